@@ -1,19 +1,90 @@
 use crate::audio;
 use serde::Serialize;
-use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Status {
     pub state: String,
     pub last_error: Option<String>,
+    /// The pre-roll input is holding the microphone open while idle.
+    pub mic_open: bool,
+    /// Input levels of the most recent recording.
+    pub input_stats: Option<audio::InputStats>,
+    /// Set when the most recent recording looked muted, too quiet or clipped.
+    pub input_warning: Option<String>,
+}
+
+impl Status {
+    /// A recording is open, capturing or paused.
+    pub fn is_recording(&self) -> bool {
+        matches!(self.state.as_str(), "Recording" | "Paused")
+    }
+}
+
+/// How the current recording was started; decides how it may end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordingMode {
+    /// Push-to-talk: stops when the hotkey is released.
+    #[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+    Hold,
+    /// Double-tap, tray or UI: stops on the next toggle, the time limit or
+    /// auto-stop, and may be paused.
+    Toggle,
+}
+
+/// Time the current recording has spent capturing, leaving out pauses.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ActiveTime {
+    accumulated: Duration,
+    resumed_at: Option<Instant>,
+}
+
+impl ActiveTime {
+    pub fn started(now: Instant) -> Self {
+        Self {
+            accumulated: Duration::ZERO,
+            resumed_at: Some(now),
+        }
+    }
+
+    pub fn pause(&mut self, now: Instant) {
+        if let Some(resumed_at) = self.resumed_at.take() {
+            self.accumulated += now.saturating_duration_since(resumed_at);
+        }
+    }
+
+    pub fn resume(&mut self, now: Instant) {
+        self.resumed_at.get_or_insert(now);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.resumed_at.is_none()
+    }
+
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        self.accumulated
+            + self
+                .resumed_at
+                .map_or(Duration::ZERO, |resumed_at| now.saturating_duration_since(resumed_at))
+    }
 }
 
 pub struct RuntimeState {
     pub status: Status,
     pub recording: Option<audio::RecordingHandle>,
-    pub recording_path: Option<PathBuf>,
+    pub recording_mode: RecordingMode,
     pub recording_token: u64,
+    /// Counts toward `recording.maxSeconds`.
+    pub active_time: ActiveTime,
+    /// Inserts the segments handed off by rollover or incremental insertion;
+    /// finishes once the recording has stopped and every earlier segment is
+    /// in, with whether any text was inserted.
+    pub segment_worker: Option<tauri::async_runtime::JoinHandle<bool>>,
+    pub warm_input: Option<audio::WarmInput>,
+    /// Bumped whenever the warm input is replaced, so a stale one closing does
+    /// not clear the indicator for its successor.
+    pub warm_input_token: u64,
 }
 
 impl RuntimeState {
@@ -22,10 +93,38 @@ impl RuntimeState {
             status: Status {
                 state: "Idle".to_string(),
                 last_error: None,
+                mic_open: false,
+                input_stats: None,
+                input_warning: None,
             },
             recording: None,
-            recording_path: None,
+            recording_mode: RecordingMode::Toggle,
             recording_token: 0,
+            active_time: ActiveTime::default(),
+            segment_worker: None,
+            warm_input: None,
+            warm_input_token: 0,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn active_time_leaves_out_pauses() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut time = ActiveTime::started(start);
+        assert_eq!(time.elapsed(at(1_000)), Duration::from_millis(1_000));
+
+        time.pause(at(1_000));
+        assert!(time.is_paused());
+        assert_eq!(time.elapsed(at(5_000)), Duration::from_millis(1_000));
+
+        time.resume(at(5_000));
+        time.resume(at(6_000));
+        assert_eq!(time.elapsed(at(7_500)), Duration::from_millis(3_500));
+    }
+}
@@ -0,0 +1,188 @@
+use crate::config::ChannelMode;
+
+/// Power ratio another channel must exceed the current one by before
+/// `ChannelMode::Loudest` switches to it (~3 dB), so near-equal channels do not
+/// flip back and forth between callbacks.
+const LOUDEST_SWITCH_RATIO: f32 = 2.0;
+
+/// Smoothing factor for the per-channel energy used by `ChannelMode::Loudest`.
+const ENERGY_SMOOTHING: f32 = 0.3;
+
+/// Scales any cpal sample to `[-1.0, 1.0]`, with unsigned formats centred on
+/// their midpoint.
+pub fn sample_to_f32<T>(sample: T) -> f32
+where
+    f32: cpal::FromSample<T>,
+{
+    <f32 as cpal::FromSample<T>>::from_sample_(sample)
+}
+
+/// Turns interleaved multi-channel input into mono according to a `ChannelMode`.
+pub struct Downmixer {
+    mode: ChannelMode,
+    channels: usize,
+    selected: usize,
+    energies: Vec<f32>,
+}
+
+impl Downmixer {
+    pub fn new(mode: ChannelMode, channel_index: u16, channels: usize) -> Self {
+        Self {
+            mode,
+            channels,
+            selected: (channel_index as usize).min(channels.saturating_sub(1)),
+            energies: vec![0.0; channels],
+        }
+    }
+
+    /// Converts one interleaved callback buffer to mono, appending to `out`.
+    pub fn process<T: Copy>(&mut self, data: &[T], to_f32: impl Fn(T) -> f32, out: &mut Vec<f32>) {
+        if self.channels == 0 {
+            return;
+        }
+        if self.channels == 1 {
+            out.extend(data.iter().map(|&s| to_f32(s)));
+            return;
+        }
+
+        match self.mode {
+            ChannelMode::Average => {
+                let scale = 1.0 / self.channels as f32;
+                for frame in data.chunks_exact(self.channels) {
+                    let sum: f32 = frame.iter().map(|&s| to_f32(s)).sum();
+                    out.push(sum * scale);
+                }
+            }
+            ChannelMode::Select => {
+                for frame in data.chunks_exact(self.channels) {
+                    out.push(to_f32(frame[self.selected]));
+                }
+            }
+            ChannelMode::Loudest => {
+                self.update_loudest(data, &to_f32);
+                for frame in data.chunks_exact(self.channels) {
+                    out.push(to_f32(frame[self.selected]));
+                }
+            }
+        }
+    }
+
+    fn update_loudest<T: Copy>(&mut self, data: &[T], to_f32: &impl Fn(T) -> f32) {
+        let frames = data.len() / self.channels;
+        if frames == 0 {
+            return;
+        }
+
+        for (ch, energy) in self.energies.iter_mut().enumerate() {
+            let sum: f32 = data
+                .chunks_exact(self.channels)
+                .map(|frame| {
+                    let s = to_f32(frame[ch]);
+                    s * s
+                })
+                .sum();
+            let mean = sum / frames as f32;
+            *energy += (mean - *energy) * ENERGY_SMOOTHING;
+        }
+
+        let (loudest, &loudest_energy) = self
+            .energies
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .expect("at least one channel");
+        if loudest != self.selected && loudest_energy > self.energies[self.selected] * LOUDEST_SWITCH_RATIO {
+            self.selected = loudest;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(mixer: &mut Downmixer, data: &[f32]) -> Vec<f32> {
+        let mut out = Vec::new();
+        mixer.process(data, |s| s, &mut out);
+        out
+    }
+
+    #[test]
+    fn averages_all_channels() {
+        let mut mixer = Downmixer::new(ChannelMode::Average, 0, 2);
+        assert_eq!(run(&mut mixer, &[1.0, 0.0, 0.5, 0.5, -1.0, 1.0]), vec![0.5, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn selects_requested_channel_and_clamps_out_of_range_index() {
+        let data = [0.1, 0.2, 0.3, 0.4, 0.5, 0.6];
+
+        let mut mixer = Downmixer::new(ChannelMode::Select, 1, 3);
+        assert_eq!(run(&mut mixer, &data), vec![0.2, 0.5]);
+
+        let mut mixer = Downmixer::new(ChannelMode::Select, 7, 3);
+        assert_eq!(run(&mut mixer, &data), vec![0.3, 0.6]);
+    }
+
+    #[test]
+    fn loudest_follows_the_channel_with_most_energy() {
+        let mut mixer = Downmixer::new(ChannelMode::Loudest, 0, 2);
+        let quiet_left: Vec<f32> = (0..64).flat_map(|_| [0.01, 0.5]).collect();
+        assert!(run(&mut mixer, &quiet_left).iter().all(|&s| s == 0.5));
+
+        // A slightly louder left channel is not enough to switch back.
+        let near_equal: Vec<f32> = (0..64).flat_map(|_| [0.55, 0.5]).collect();
+        for _ in 0..10 {
+            assert!(run(&mut mixer, &near_equal).iter().all(|&s| s == 0.5));
+        }
+
+        let loud_left: Vec<f32> = (0..64).flat_map(|_| [0.9, 0.05]).collect();
+        let mut last = Vec::new();
+        for _ in 0..10 {
+            last = run(&mut mixer, &loud_left);
+        }
+        assert!(last.iter().all(|&s| s == 0.9));
+    }
+
+    #[test]
+    fn mono_input_is_passed_through_with_conversion() {
+        let mut mixer = Downmixer::new(ChannelMode::Loudest, 3, 1);
+        let mut out = Vec::new();
+        mixer.process(&[i16::MIN, 0], sample_to_f32, &mut out);
+        assert_eq!(out, vec![-1.0, 0.0]);
+    }
+
+    fn assert_scaling<T: Copy>(min: T, mid: T, max: T)
+    where
+        f32: cpal::FromSample<T>,
+    {
+        assert_eq!(sample_to_f32(min), -1.0);
+        assert_eq!(sample_to_f32(mid), 0.0);
+        assert!((sample_to_f32(max) - 1.0).abs() < 1e-2, "max scaled to {}", sample_to_f32(max));
+    }
+
+    #[test]
+    fn signed_integer_formats_scale_to_unit_range() {
+        assert_scaling(i8::MIN, 0i8, i8::MAX);
+        assert_scaling(i16::MIN, 0i16, i16::MAX);
+        assert_scaling(i32::MIN, 0i32, i32::MAX);
+        assert_scaling(i64::MIN, 0i64, i64::MAX);
+        assert_eq!(sample_to_f32(i16::MIN / 2), -0.5);
+    }
+
+    #[test]
+    fn unsigned_integer_formats_are_centred_on_their_midpoint() {
+        assert_scaling(u8::MIN, 128u8, u8::MAX);
+        assert_scaling(u16::MIN, 32_768u16, u16::MAX);
+        assert_scaling(u32::MIN, 1u32 << 31, u32::MAX);
+        assert_scaling(u64::MIN, 1u64 << 63, u64::MAX);
+        assert_eq!(sample_to_f32(64u8), -0.5);
+    }
+
+    #[test]
+    fn float_formats_pass_through() {
+        assert_eq!(sample_to_f32(0.25f32), 0.25);
+        assert_eq!(sample_to_f32(-0.5f64), -0.5);
+        assert_eq!(sample_to_f32(1.0f64), 1.0);
+    }
+}
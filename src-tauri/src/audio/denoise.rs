@@ -0,0 +1,157 @@
+use super::resample::Resampler;
+use crate::config::ResampleQuality;
+use nnnoiseless::DenoiseState;
+
+/// RNNoise only runs on 48 kHz audio.
+pub const DENOISE_SAMPLE_RATE: u32 = 48_000;
+
+const FRAME_SIZE: usize = DenoiseState::FRAME_SIZE;
+
+/// Noise suppression at whatever rate the device delivers: converts to 48 kHz
+/// when needed and runs the denoiser there.
+pub struct DenoiseStage {
+    resampler: Option<Resampler>,
+    resampled: Vec<f32>,
+    denoiser: Denoiser,
+}
+
+impl DenoiseStage {
+    pub fn new(quality: ResampleQuality, in_rate: u32) -> Result<Self, String> {
+        let resampler = if in_rate == DENOISE_SAMPLE_RATE {
+            None
+        } else {
+            Some(Resampler::new(quality, in_rate, DENOISE_SAMPLE_RATE)?)
+        };
+        Ok(Self {
+            resampler,
+            resampled: Vec::new(),
+            denoiser: Denoiser::new(),
+        })
+    }
+
+    /// Denoises device-rate `samples`, appending 48 kHz output to `out`.
+    pub fn process(&mut self, samples: &[f32], out: &mut Vec<f32>) -> Result<(), String> {
+        match self.resampler.as_mut() {
+            Some(resampler) => {
+                self.resampled.clear();
+                resampler.process(samples, &mut self.resampled)?;
+                self.denoiser.process(&self.resampled, out);
+            }
+            None => self.denoiser.process(samples, out),
+        }
+        Ok(())
+    }
+
+    pub fn finish(&mut self, out: &mut Vec<f32>) -> Result<(), String> {
+        if let Some(resampler) = self.resampler.as_mut() {
+            self.resampled.clear();
+            resampler.finish(&mut self.resampled)?;
+            self.denoiser.process(&self.resampled, out);
+        }
+        self.denoiser.finish(out);
+        Ok(())
+    }
+}
+
+/// Streaming RNNoise (nnnoiseless) noise suppressor for 48 kHz mono audio.
+pub struct Denoiser {
+    state: Box<DenoiseState<'static>>,
+    input: Vec<f32>,
+    frame_out: Vec<f32>,
+    first_frame: bool,
+}
+
+impl Denoiser {
+    pub fn new() -> Self {
+        Self {
+            state: DenoiseState::new(),
+            input: Vec::with_capacity(FRAME_SIZE),
+            frame_out: vec![0.0; FRAME_SIZE],
+            first_frame: true,
+        }
+    }
+
+    /// Denoises `samples`, appending whole processed frames to `out`.
+    pub fn process(&mut self, samples: &[f32], out: &mut Vec<f32>) {
+        let mut rest = samples;
+        while !rest.is_empty() {
+            let take = (FRAME_SIZE - self.input.len()).min(rest.len());
+            self.input.extend_from_slice(&rest[..take]);
+            rest = &rest[take..];
+            if self.input.len() == FRAME_SIZE {
+                self.process_frame(out);
+            }
+        }
+    }
+
+    /// Flushes the last partial frame, padded with silence and cut back to length.
+    pub fn finish(&mut self, out: &mut Vec<f32>) {
+        let remaining = self.input.len();
+        if remaining == 0 {
+            return;
+        }
+        self.input.resize(FRAME_SIZE, 0.0);
+        let start = out.len();
+        self.process_frame(out);
+        out.truncate(start + remaining);
+    }
+
+    fn process_frame(&mut self, out: &mut Vec<f32>) {
+        // nnnoiseless expects samples in the i16 range.
+        for s in self.input.iter_mut() {
+            *s *= i16::MAX as f32;
+        }
+        self.state.process_frame(&mut self.frame_out, &self.input);
+        self.input.clear();
+
+        // The first frame carries the model's warm-up transient.
+        if self.first_frame {
+            self.first_frame = false;
+            self.frame_out.fill(0.0);
+        }
+        out.extend(self.frame_out.iter().map(|s| s / i16::MAX as f32));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    fn run(input: &[f32], chunk: usize) -> Vec<f32> {
+        let mut d = Denoiser::new();
+        let mut out = Vec::new();
+        for c in input.chunks(chunk) {
+            d.process(c, &mut out);
+        }
+        d.finish(&mut out);
+        out
+    }
+
+    #[test]
+    fn preserves_length() {
+        let input = vec![0.0; 48_000 + 123];
+        assert_eq!(run(&input, 441).len(), input.len());
+        assert_eq!(run(&input[..FRAME_SIZE * 3], 1_000).len(), FRAME_SIZE * 3);
+    }
+
+    #[test]
+    fn suppresses_steady_background_noise() {
+        // Deterministic white-ish noise from a small LCG.
+        let mut seed = 0x1234_5678u32;
+        let noise: Vec<f32> = (0..DENOISE_SAMPLE_RATE as usize * 2)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                ((seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5) * 0.05
+            })
+            .collect();
+
+        let out = run(&noise, 480);
+        let second_half = DENOISE_SAMPLE_RATE as usize;
+        let reduction_db = 20.0 * (rms(&out[second_half..]) / rms(&noise[second_half..])).log10();
+        assert!(reduction_db < -6.0, "noise only reduced by {reduction_db} dB");
+    }
+}
@@ -0,0 +1,156 @@
+use cpal::traits::{DeviceTrait, HostTrait};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputDeviceInfo {
+    pub name: String,
+    pub is_default: bool,
+    pub configs: Vec<SupportedInputConfig>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupportedInputConfig {
+    pub channels: u16,
+    pub min_sample_rate: u32,
+    pub max_sample_rate: u32,
+    pub sample_format: String,
+}
+
+pub fn list_input_devices() -> Result<Vec<InputDeviceInfo>, String> {
+    let host = cpal::default_host();
+    let default_name = host.default_input_device().and_then(|d| d.name().ok());
+    let devices = host
+        .input_devices()
+        .map_err(|e| format!("failed to enumerate input devices: {e}"))?;
+
+    let mut out = Vec::new();
+    for device in devices {
+        let Ok(name) = device.name() else {
+            continue;
+        };
+        let configs = device
+            .supported_input_configs()
+            .map(|configs| {
+                configs
+                    .map(|c| SupportedInputConfig {
+                        channels: c.channels(),
+                        min_sample_rate: c.min_sample_rate().0,
+                        max_sample_rate: c.max_sample_rate().0,
+                        sample_format: c.sample_format().to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        out.push(InputDeviceInfo {
+            is_default: default_name.as_deref() == Some(name.as_str()),
+            name,
+            configs,
+        });
+    }
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputDeviceInfo {
+    pub name: String,
+    pub is_default: bool,
+}
+
+pub fn list_output_devices() -> Result<Vec<OutputDeviceInfo>, String> {
+    let host = cpal::default_host();
+    let default_name = host.default_output_device().and_then(|d| d.name().ok());
+    let devices = host
+        .output_devices()
+        .map_err(|e| format!("failed to enumerate output devices: {e}"))?;
+
+    Ok(devices
+        .filter_map(|device| device.name().ok())
+        .map(|name| OutputDeviceInfo {
+            is_default: default_name.as_deref() == Some(name.as_str()),
+            name,
+        })
+        .collect())
+}
+
+/// Finds an output device by name, or the default output device for `None`.
+pub fn select_output_device(host: &cpal::Host, requested: Option<&str>) -> Result<(cpal::Device, String), String> {
+    match requested.map(str::trim).filter(|name| !name.is_empty()) {
+        Some(requested) => host
+            .output_devices()
+            .map_err(|e| format!("failed to enumerate output devices: {e}"))?
+            .find(|d| d.name().is_ok_and(|name| name == requested))
+            .map(|device| (device, requested.to_string()))
+            .ok_or_else(|| format!("output device \"{requested}\" not found")),
+        None => {
+            let device = host
+                .default_output_device()
+                .ok_or_else(|| "no default output device".to_string())?;
+            let name = device.name().unwrap_or_else(|_| "default".to_string());
+            Ok((device, name))
+        }
+    }
+}
+
+/// Input device picked for a recording.
+pub struct SelectedDevice {
+    pub device: cpal::Device,
+    pub name: String,
+    /// Set when the configured device was not found and the default was used instead.
+    pub fell_back_from: Option<String>,
+}
+
+/// Finds the configured input device by name, falling back to the default input
+/// device when it is missing.
+pub fn select_input_device(host: &cpal::Host, requested: Option<&str>) -> Result<SelectedDevice, String> {
+    let requested = requested.map(str::trim).filter(|name| !name.is_empty());
+
+    if let Some(requested) = requested {
+        let found = host
+            .input_devices()
+            .map_err(|e| format!("failed to enumerate input devices: {e}"))?
+            .find(|d| d.name().is_ok_and(|name| name == requested));
+        if let Some(device) = found {
+            return Ok(SelectedDevice {
+                device,
+                name: requested.to_string(),
+                fell_back_from: None,
+            });
+        }
+    }
+
+    let device = host
+        .default_input_device()
+        .ok_or_else(|| match requested {
+            Some(name) => format!("input device \"{name}\" not found and no default input device"),
+            None => "no default input device".to_string(),
+        })?;
+    let name = device.name().unwrap_or_else(|_| "default".to_string());
+    Ok(SelectedDevice {
+        device,
+        name,
+        fell_back_from: requested.map(str::to_string),
+    })
+}
+
+/// Picks a replacement for an input device that went away: the default input
+/// device if it is a different one, otherwise the first other input device.
+pub fn next_input_device(host: &cpal::Host, lost: &str) -> Option<SelectedDevice> {
+    let default = host
+        .default_input_device()
+        .and_then(|d| d.name().ok().map(|name| (d, name)))
+        .filter(|(_, name)| name != lost);
+    let (device, name) = default.or_else(|| {
+        host.input_devices()
+            .ok()?
+            .filter_map(|d| d.name().ok().map(|name| (d, name)))
+            .find(|(_, name)| name != lost)
+    })?;
+    Some(SelectedDevice {
+        device,
+        name,
+        fell_back_from: None,
+    })
+}
@@ -0,0 +1,255 @@
+use super::vad::db_to_linear;
+use crate::config::DspConfig;
+
+/// Block length the automatic gain measures loudness over.
+const AGC_BLOCK_MS: u32 = 10;
+/// Largest attenuation the automatic gain applies to loud input.
+const AGC_MIN_GAIN_DB: f32 = -12.0;
+/// Time constants of the gain when it has to drop (loud onset) or rise
+/// (quiet speech). Rising takes seconds so a pause cannot pump it up.
+const AGC_ATTACK_MS: f32 = 50.0;
+const AGC_RELEASE_MS: f32 = 2_000.0;
+/// Level above which the limiter starts to bend the signal.
+const LIMITER_THRESHOLD: f32 = 0.8;
+
+/// Input conditioning applied to the 16 kHz signal.
+///
+/// The high-pass runs first so DC offset and rumble do not look like speech to
+/// the VAD; gain and limiting run last, on the audio that is actually kept.
+pub struct DspChain {
+    high_pass: Option<HighPass>,
+    agc: Option<AutoGain>,
+    limiter: bool,
+}
+
+impl DspChain {
+    /// `speech_db` is the VAD threshold; the automatic gain only follows
+    /// blocks at least that loud.
+    pub fn new(cfg: &DspConfig, speech_db: f32, sample_rate: u32) -> Self {
+        Self {
+            high_pass: cfg.high_pass.then(|| HighPass::new(cfg.high_pass_hz, sample_rate)),
+            agc: cfg
+                .normalize
+                .then(|| AutoGain::new(sample_rate, cfg.target_db, cfg.max_gain_db, speech_db)),
+            limiter: cfg.limiter,
+        }
+    }
+
+    pub fn pre_process(&mut self, samples: &mut [f32]) {
+        if let Some(hp) = self.high_pass.as_mut() {
+            hp.process(samples);
+        }
+    }
+
+    pub fn post_process(&mut self, samples: &mut [f32]) {
+        if let Some(agc) = self.agc.as_mut() {
+            agc.process(samples);
+        }
+        if self.limiter {
+            soft_limit(samples);
+        }
+    }
+}
+
+/// Second-order Butterworth high-pass (RBJ biquad).
+pub struct HighPass {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl HighPass {
+    pub fn new(cutoff_hz: f32, sample_rate: u32) -> Self {
+        let cutoff = cutoff_hz.clamp(1.0, sample_rate as f32 * 0.45);
+        let w0 = 2.0 * std::f32::consts::PI * cutoff / sample_rate as f32;
+        let alpha = w0.sin() / (2.0 * std::f32::consts::FRAC_1_SQRT_2);
+        let cos = w0.cos();
+        let a0 = 1.0 + alpha;
+        Self {
+            b0: (1.0 + cos) / 2.0 / a0,
+            b1: -(1.0 + cos) / a0,
+            b2: (1.0 + cos) / 2.0 / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha) / a0,
+            x1: 0.0,
+            x2: 0.0,
+            y1: 0.0,
+            y2: 0.0,
+        }
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for s in samples {
+            let x = *s;
+            let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2 - self.a1 * self.y1 - self.a2 * self.y2;
+            self.x2 = self.x1;
+            self.x1 = x;
+            self.y2 = self.y1;
+            self.y1 = y;
+            *s = y;
+        }
+    }
+}
+
+/// Automatic gain that moves speech toward a target RMS level. Loudness is
+/// measured over whole blocks, however the input is split; blocks below the
+/// speech gate (pauses, room noise) leave the gain where it is.
+pub struct AutoGain {
+    block_len: usize,
+    target: f32,
+    gate: f32,
+    min_gain: f32,
+    max_gain: f32,
+    attack: f32,
+    release: f32,
+    gain: f32,
+    /// Gain applied to the current sample, ramping toward `gain`.
+    applied: f32,
+    step: f32,
+    steps_left: usize,
+    sum_sq: f32,
+    filled: usize,
+}
+
+impl AutoGain {
+    pub fn new(sample_rate: u32, target_db: f32, max_gain_db: f32, gate_db: f32) -> Self {
+        let block_len = (sample_rate * AGC_BLOCK_MS / 1_000).max(1) as usize;
+        let block_ms = block_len as f32 * 1_000.0 / sample_rate as f32;
+        let smoothing = |time_constant_ms: f32| 1.0 - (-block_ms / time_constant_ms).exp();
+        Self {
+            block_len,
+            target: db_to_linear(target_db),
+            gate: db_to_linear(gate_db),
+            min_gain: db_to_linear(AGC_MIN_GAIN_DB),
+            max_gain: db_to_linear(max_gain_db.max(0.0)),
+            attack: smoothing(AGC_ATTACK_MS),
+            release: smoothing(AGC_RELEASE_MS),
+            gain: 1.0,
+            applied: 1.0,
+            step: 0.0,
+            steps_left: 0,
+            sum_sq: 0.0,
+            filled: 0,
+        }
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for s in samples {
+            self.sum_sq += *s * *s;
+            self.filled += 1;
+            if self.steps_left > 0 {
+                self.applied += self.step;
+                self.steps_left -= 1;
+            }
+            *s *= self.applied;
+
+            if self.filled == self.block_len {
+                let level = (self.sum_sq / self.block_len as f32).sqrt();
+                self.sum_sq = 0.0;
+                self.filled = 0;
+                if level >= self.gate {
+                    let desired = (self.target / level).clamp(self.min_gain, self.max_gain);
+                    let speed = if desired < self.gain { self.attack } else { self.release };
+                    self.gain += (desired - self.gain) * speed;
+                }
+                // Ramp across the next block so gain changes do not click.
+                self.step = (self.gain - self.applied) / self.block_len as f32;
+                self.steps_left = self.block_len;
+            }
+        }
+    }
+}
+
+/// Passes samples below `LIMITER_THRESHOLD` unchanged and bends louder ones
+/// smoothly toward full scale instead of hard clipping.
+pub fn soft_limit(samples: &mut [f32]) {
+    let headroom = 1.0 - LIMITER_THRESHOLD;
+    for s in samples {
+        let magnitude = s.abs();
+        if magnitude > LIMITER_THRESHOLD {
+            let bent = LIMITER_THRESHOLD + headroom * ((magnitude - LIMITER_THRESHOLD) / headroom).tanh();
+            *s = bent.copysign(*s);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::vad::rms;
+    use super::*;
+
+    const RATE: u32 = 16_000;
+
+    fn sine(freq: f32, amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * freq * i as f32 / RATE as f32).sin())
+            .collect()
+    }
+
+    fn db(x: f32) -> f32 {
+        20.0 * x.log10()
+    }
+
+    #[test]
+    fn high_pass_removes_dc_and_rumble_but_keeps_speech_band() {
+        let mut dc = vec![0.2; 16_000];
+        HighPass::new(80.0, RATE).process(&mut dc);
+        assert!(dc[8_000..].iter().all(|s| s.abs() < 1e-4));
+
+        let mut rumble = sine(20.0, 0.5, 16_000);
+        HighPass::new(80.0, RATE).process(&mut rumble);
+        assert!(db(rms(&rumble[8_000..]) / rms(&sine(20.0, 0.5, 8_000))) < -20.0);
+
+        let mut voice = sine(1_000.0, 0.5, 16_000);
+        HighPass::new(80.0, RATE).process(&mut voice);
+        assert!(db(rms(&voice[8_000..]) / rms(&sine(1_000.0, 0.5, 8_000))).abs() < 0.1);
+    }
+
+    #[test]
+    fn auto_gain_brings_quiet_speech_toward_target() {
+        let mut agc = AutoGain::new(RATE, -20.0, 30.0, -40.0);
+        let mut quiet = sine(300.0, 0.02, RATE as usize * 12);
+        for chunk in quiet.chunks_mut(333) {
+            agc.process(chunk);
+        }
+        let level = db(rms(&quiet[RATE as usize * 10..]));
+        assert!((level + 20.0).abs() < 1.0, "level {level} dB");
+
+        // Rising is slow: half a second in, the gain is still well short.
+        let mut agc = AutoGain::new(RATE, -20.0, 30.0, -40.0);
+        agc.process(&mut sine(300.0, 0.02, RATE as usize / 2));
+        assert!(db(agc.gain) < 10.0, "gain {} dB", db(agc.gain));
+    }
+
+    #[test]
+    fn auto_gain_respects_max_gain_and_ignores_background() {
+        let mut agc = AutoGain::new(RATE, -20.0, 6.0, -40.0);
+        let mut quiet = sine(300.0, 0.02, RATE as usize * 12);
+        agc.process(&mut quiet);
+        assert!((db(agc.gain) - 6.0).abs() < 0.1);
+
+        // Room noise between words, below the speech gate, leaves the gain
+        // where it was.
+        let mut agc = AutoGain::new(RATE, -20.0, 30.0, -40.0);
+        let mut room = sine(300.0, 0.0045, RATE as usize * 3);
+        agc.process(&mut room);
+        assert_eq!(agc.gain, 1.0);
+    }
+
+    #[test]
+    fn soft_limiter_is_transparent_below_threshold_and_never_clips() {
+        let mut samples = vec![0.5, -0.79, 0.81, 1.0, -3.0, 10.0];
+        soft_limit(&mut samples);
+        assert_eq!(&samples[..2], &[0.5, -0.79]);
+        assert!(samples[2] > 0.8 && samples[2] < 0.81);
+        assert!(samples[3..].iter().all(|s| s.abs() <= 1.0));
+        assert!(samples[4] < 0.0);
+        assert!(samples[3] < samples[5].abs());
+    }
+}
@@ -0,0 +1,222 @@
+use super::convert::Downmixer;
+use super::{run_pipeline, Capture, FinishedRecording, RecordingOptions, RecordingTarget};
+use crate::config;
+use std::path::Path;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as DecodeError;
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// Decodes an audio file (WAV, FLAC, Ogg Vorbis, MP3, AAC/M4A) and runs it
+/// through the same downmix, noise suppression, resampling and DSP as a live
+/// recording, producing a 16 kHz mono WAV in memory.
+pub fn decode_file(path: &Path, cfg: &config::RecordingConfig) -> Result<FinishedRecording, String> {
+    let (format, sample_rate) = open_track(path)?;
+
+    let (tx, rx) = crossbeam_channel::unbounded::<Capture>();
+    let (events, _) = crossbeam_channel::unbounded();
+    let options = RecordingOptions {
+        config: cfg.clone(),
+        events,
+        raw_copy_path: None,
+        channels_copy_path: None,
+        warm_input: None,
+        segments: None,
+    };
+    let channel_mode = cfg.channel_mode;
+    let channel_index = cfg.channel_index;
+    let decoder = std::thread::spawn(move || {
+        decode_into(format, sample_rate, channel_mode, channel_index, |capture| tx.send(capture).is_ok())
+    });
+
+    let encoded = run_pipeline(rx, RecordingTarget::Memory, sample_rate, options, None);
+    decoder
+        .join()
+        .map_err(|_| "audio decoder thread panicked".to_string())?
+        .map_err(|e| format!("failed to decode {}: {e}", path.display()))?;
+    encoded
+}
+
+/// How long the audio in a file lasts, going by its header. `None` when the
+/// format does not say (some MP3 and Ogg files) without decoding it all.
+pub fn file_seconds(path: &Path) -> Result<Option<f64>, String> {
+    let (format, sample_rate) = open_track(path)?;
+    let frames = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .and_then(|t| t.codec_params.n_frames);
+    Ok(frames.map(|frames| frames as f64 / sample_rate as f64))
+}
+
+/// Decodes a whole file to mono at its own sample rate.
+pub(super) fn read_mono(path: &Path, cfg: &config::RecordingConfig) -> Result<(Vec<f32>, u32), String> {
+    let (format, sample_rate) = open_track(path)?;
+    let mut samples = Vec::new();
+    let mut rate_changed = false;
+    decode_into(format, sample_rate, cfg.channel_mode, cfg.channel_index, |capture| match capture {
+        Capture::Samples(mono) => {
+            samples.extend(mono);
+            true
+        }
+        _ => {
+            rate_changed = true;
+            false
+        }
+    })
+    .map_err(|e| format!("failed to decode {}: {e}", path.display()))?;
+    if rate_changed {
+        return Err(format!("{} changes sample rate part way through", path.display()));
+    }
+    Ok((samples, sample_rate))
+}
+
+/// Probes `path` and returns its reader along with the first audio track's rate.
+fn open_track(path: &Path) -> Result<(Box<dyn FormatReader>, u32), String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("failed to open {}: {e}", path.display()))?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(extension);
+    }
+    let probed = symphonia::default::get_probe()
+        .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(|e| format!("unsupported audio file {}: {e}", path.display()))?;
+
+    let track = probed
+        .format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| format!("{} has no audio track", path.display()))?;
+    let sample_rate = track
+        .codec_params
+        .sample_rate
+        .ok_or_else(|| format!("{} does not declare a sample rate", path.display()))?;
+    Ok((probed.format, sample_rate))
+}
+
+/// Hands mono samples from the first audio track to `emit`, announcing any
+/// change of sample rate mid-stream the way a device switch does. Stops early
+/// once `emit` returns false.
+fn decode_into(
+    mut format: Box<dyn FormatReader>,
+    mut sample_rate: u32,
+    channel_mode: config::ChannelMode,
+    channel_index: u16,
+    mut emit: impl FnMut(Capture) -> bool,
+) -> Result<(), String> {
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| "no audio track".to_string())?;
+    let track_id = track.id;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| format!("unsupported codec: {e}"))?;
+
+    let mut buffer: Option<SampleBuffer<f32>> = None;
+    let mut mixer: Option<Downmixer> = None;
+    let mut channels = 0;
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(DecodeError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e.to_string()),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // A corrupt packet is skipped rather than failing the whole file.
+            Err(DecodeError::DecodeError(_)) => continue,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let spec = *decoded.spec();
+        if spec.rate != sample_rate {
+            sample_rate = spec.rate;
+            if !emit(Capture::RateChanged(sample_rate)) {
+                return Ok(());
+            }
+        }
+        if mixer.is_none() || spec.channels.count() != channels {
+            channels = spec.channels.count();
+            mixer = Some(Downmixer::new(channel_mode, channel_index, channels));
+        }
+        let capacity = decoded.capacity() as u64;
+        if buffer.as_ref().is_some_and(|b| (b.capacity() as u64) < capacity * channels as u64) {
+            buffer = None;
+        }
+        let samples = buffer.get_or_insert_with(|| SampleBuffer::new(capacity, spec));
+        samples.copy_interleaved_ref(decoded);
+
+        let mut mono = Vec::with_capacity(samples.len() / channels.max(1));
+        if let Some(mixer) = mixer.as_mut() {
+            mixer.process(samples.samples(), |s| s, &mut mono);
+        }
+        if !emit(Capture::Samples(mono)) {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::RecordedAudio;
+
+    #[test]
+    fn decodes_stereo_file_to_16k_mono() {
+        let path = std::env::temp_dir().join(format!("voicedictation-file-test-{}.wav", std::process::id()));
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 44_100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for i in 0..44_100 * 2 {
+            let s = (0.5 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 44_100.0).sin() * i16::MAX as f32) as i16;
+            writer.write_sample(s).unwrap();
+            writer.write_sample(s).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let cfg = config::RecordingConfig {
+            trim: config::TrimConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let seconds = file_seconds(&path);
+        let finished = decode_file(&path, &cfg);
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(seconds, Ok(Some(2.0)));
+        let finished = finished.unwrap();
+
+        assert!((finished.stats.seconds - 2.0).abs() < 0.01);
+        let RecordedAudio::Memory(bytes) = finished.audio else {
+            panic!("expected an in-memory wav");
+        };
+        let reader = hound::WavReader::new(std::io::Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.spec().sample_rate, 16_000);
+        assert_eq!(reader.spec().channels, 1);
+        assert!((reader.duration() as i64 - 32_000).abs() < 200, "{} samples", reader.duration());
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_audio() {
+        let path = std::env::temp_dir().join(format!("voicedictation-file-test-{}.txt", std::process::id()));
+        std::fs::write(&path, b"not audio at all").unwrap();
+        let result = decode_file(&path, &config::RecordingConfig::default());
+        let _ = std::fs::remove_file(&path);
+        assert!(result.unwrap_err().contains("unsupported audio file"));
+    }
+}
@@ -0,0 +1,182 @@
+use cpal::traits::{DeviceTrait, HostTrait};
+use serde::Serialize;
+
+/// Listed name of the PulseAudio/PipeWire monitor of the default output.
+#[cfg(target_os = "linux")]
+pub const DEFAULT_MONITOR: &str = "Default output monitor";
+
+/// Input device name fragments that identify loopback or monitor devices:
+/// ALSA/PulseAudio monitors, the `snd-aloop` loopback card and common virtual
+/// drivers on macOS.
+#[cfg(not(windows))]
+const LOOPBACK_NAME_HINTS: [&str; 4] = ["monitor", "loopback", "blackhole", "soundflower"];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoopbackDeviceInfo {
+    pub name: String,
+    pub is_default: bool,
+}
+
+/// Devices that capture what is playing rather than a microphone. On Windows
+/// these are the output devices themselves (WASAPI loopback).
+pub fn list_loopback_devices() -> Result<Vec<LoopbackDeviceInfo>, String> {
+    let host = cpal::default_host();
+
+    #[cfg(windows)]
+    {
+        let default_name = host.default_output_device().and_then(|d| d.name().ok());
+        let devices = host
+            .output_devices()
+            .map_err(|e| format!("failed to enumerate output devices: {e}"))?;
+        Ok(devices
+            .filter_map(|d| d.name().ok())
+            .map(|name| LoopbackDeviceInfo {
+                is_default: default_name.as_deref() == Some(name.as_str()),
+                name,
+            })
+            .collect())
+    }
+
+    #[cfg(not(windows))]
+    {
+        let names: Vec<String> = host
+            .input_devices()
+            .map_err(|e| format!("failed to enumerate input devices: {e}"))?
+            .filter_map(|d| d.name().ok())
+            .collect();
+        let mut out = Vec::new();
+        #[cfg(target_os = "linux")]
+        if super::monitor::available(std::env::var_os("PATH").as_deref()) {
+            out.push(LoopbackDeviceInfo {
+                name: DEFAULT_MONITOR.to_string(),
+                is_default: true,
+            });
+        }
+        for name in names.into_iter().filter(|name| is_loopback_name(name)) {
+            out.push(LoopbackDeviceInfo {
+                is_default: out.is_empty(),
+                name,
+            });
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(not(windows))]
+fn is_loopback_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    LOOPBACK_NAME_HINTS.iter().any(|hint| name.contains(hint))
+}
+
+/// A device to capture playback from.
+pub enum LoopbackDevice {
+    /// Opened through cpal; on Windows an output device, opened with its
+    /// output config, which makes WASAPI loop it back.
+    Device { device: cpal::Device, name: String },
+    /// A PulseAudio or PipeWire monitor source, recorded through `parec`.
+    #[cfg(target_os = "linux")]
+    Monitor { source: String, name: String },
+}
+
+/// The config to open a cpal loopback device's capture stream with.
+pub fn stream_config(device: &cpal::Device, name: &str) -> Result<cpal::SupportedStreamConfig, String> {
+    #[cfg(windows)]
+    let config = device.default_output_config();
+    #[cfg(not(windows))]
+    let config = device.default_input_config();
+    config.map_err(|e| format!("failed to get config for loopback device \"{name}\": {e}"))
+}
+
+/// Finds the configured loopback device; `None` uses the default output's
+/// loopback (Windows), its monitor (Linux) or the first listed device.
+pub fn select_loopback_device(host: &cpal::Host, requested: Option<&str>) -> Result<LoopbackDevice, String> {
+    let requested = requested.map(str::trim).filter(|name| !name.is_empty());
+
+    #[cfg(windows)]
+    {
+        let device = match requested {
+            Some(name) => host
+                .output_devices()
+                .map_err(|e| format!("failed to enumerate output devices: {e}"))?
+                .find(|d| d.name().is_ok_and(|n| n == name))
+                .ok_or_else(|| format!("output device \"{name}\" not found for loopback capture"))?,
+            None => host
+                .default_output_device()
+                .ok_or_else(|| "no default output device for loopback capture".to_string())?,
+        };
+        let name = device.name().unwrap_or_else(|_| "default output".to_string());
+        Ok(LoopbackDevice::Device { device, name })
+    }
+
+    #[cfg(not(windows))]
+    {
+        let inputs = || {
+            host.input_devices()
+                .map_err(|e| format!("failed to enumerate input devices: {e}"))
+        };
+        if let Some(name) = requested {
+            if let Some(device) = inputs()?.find(|d| d.name().is_ok_and(|n| n == name)) {
+                return Ok(LoopbackDevice::Device {
+                    device,
+                    name: name.to_string(),
+                });
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        if let Some(source) = pulse_source_for(requested) {
+            return Ok(LoopbackDevice::Monitor {
+                source,
+                name: requested.unwrap_or(DEFAULT_MONITOR).to_string(),
+            });
+        }
+
+        if let Some(name) = requested {
+            return Err(format!("loopback device \"{name}\" not found"));
+        }
+        let (device, name) = inputs()?
+            .filter_map(|d| d.name().ok().map(|name| (d, name)))
+            .find(|(_, name)| is_loopback_name(name))
+            .ok_or_else(|| "no loopback or monitor device found".to_string())?;
+        Ok(LoopbackDevice::Device { device, name })
+    }
+}
+
+/// The PulseAudio source for a requested loopback name that is not an ALSA
+/// device: the default monitor, or a monitor source named directly
+/// (e.g. `alsa_output.pci-0000_00_1f.3.analog-stereo.monitor`).
+#[cfg(target_os = "linux")]
+fn pulse_source_for(requested: Option<&str>) -> Option<String> {
+    match requested {
+        None | Some(DEFAULT_MONITOR) => Some("@DEFAULT_MONITOR@".to_string()),
+        Some(name) if name.ends_with(".monitor") => Some(name.to_string()),
+        Some(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(windows))]
+    #[test]
+    fn recognizes_loopback_device_names() {
+        assert!(is_loopback_name("Monitor of Built-in Audio Analog Stereo"));
+        assert!(is_loopback_name("hw:CARD=Loopback,DEV=1"));
+        assert!(is_loopback_name("BlackHole 2ch"));
+        assert!(!is_loopback_name("USB PnP Sound Device"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn maps_monitor_names_to_pulse_sources() {
+        assert_eq!(pulse_source_for(None).as_deref(), Some("@DEFAULT_MONITOR@"));
+        assert_eq!(pulse_source_for(Some(DEFAULT_MONITOR)).as_deref(), Some("@DEFAULT_MONITOR@"));
+        assert_eq!(
+            pulse_source_for(Some("alsa_output.usb-headset.analog-stereo.monitor")).as_deref(),
+            Some("alsa_output.usb-headset.analog-stereo.monitor")
+        );
+        assert_eq!(pulse_source_for(Some("hw:CARD=Loopback,DEV=1")), None);
+    }
+}
@@ -0,0 +1,372 @@
+use super::resample::Resampler;
+use super::ring::RingReader;
+use crate::config;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::time::Instant;
+
+/// Rate the input device and system audio are mixed at.
+pub const MIX_SAMPLE_RATE: u32 = 48_000;
+/// How long after arrival audio is mixed, so a source delivering a little
+/// later than the other is still mixed with what was captured with it.
+const MIX_DELAY_MS: u64 = 60;
+/// How far a source may stray from its arrival times before it is put back in
+/// line: a gap (loopback devices deliver nothing while nothing is playing, and
+/// slow clocks fall behind) becomes silence, and a source running ahead loses
+/// the excess.
+const DRIFT_TOLERANCE_MS: u64 = 40;
+
+type ChannelsCopy = hound::WavWriter<std::io::BufWriter<std::fs::File>>;
+
+/// Mixes the input device with system audio. Both are brought to
+/// `MIX_SAMPLE_RATE` and placed on a shared timeline by arrival time, so
+/// audio captured at the same moment is mixed together however much either
+/// source has buffered, and clock drift between the devices is corrected.
+pub struct Mixer {
+    microphone: Lane,
+    system: Lane,
+    /// Timeline position of the next mixed sample.
+    emitted: u64,
+    delay: u64,
+    tolerance: u64,
+    started: Instant,
+    channels_copy: Option<ChannelsCopy>,
+}
+
+impl Mixer {
+    /// `microphone` and `system` are each an input's ring and rate. With
+    /// `channels_copy`, both sources are also written there as a stereo WAV.
+    pub fn new(
+        cfg: &config::RecordingConfig,
+        microphone: (RingReader, u32),
+        system: (RingReader, u32),
+        channels_copy: Option<&Path>,
+    ) -> Result<Self, String> {
+        let channels_copy = match channels_copy {
+            Some(path) => Some(
+                hound::WavWriter::create(
+                    path,
+                    hound::WavSpec {
+                        channels: 2,
+                        sample_rate: MIX_SAMPLE_RATE,
+                        bits_per_sample: 16,
+                        sample_format: hound::SampleFormat::Int,
+                    },
+                )
+                .map_err(|e| format!("failed to create wav {}: {e}", path.display()))?,
+            ),
+            None => None,
+        };
+        Ok(Self {
+            microphone: Lane::new(cfg, microphone, cfg.meeting.microphone_gain_db)?,
+            system: Lane::new(cfg, system, cfg.meeting.system_gain_db)?,
+            emitted: 0,
+            delay: MIX_SAMPLE_RATE as u64 * MIX_DELAY_MS / 1_000,
+            tolerance: MIX_SAMPLE_RATE as u64 * DRIFT_TOLERANCE_MS / 1_000,
+            started: Instant::now(),
+            channels_copy,
+        })
+    }
+
+    /// Throws away what either input buffered before mixing began and starts
+    /// the timeline, so the two start together.
+    pub fn align(&mut self) {
+        self.microphone.discard();
+        self.system.discard();
+        self.emitted = 0;
+        self.started = Instant::now();
+    }
+
+    /// Appends the mix of the timeline up to `MIX_DELAY_MS` ago to `out`.
+    pub fn mix(&mut self, out: &mut Vec<f32>) -> Result<(), String> {
+        self.mix_at(self.now(), out)
+    }
+
+    /// Mixes what is left once both inputs have stopped, padding the shorter
+    /// source, and finalizes the stereo copy.
+    pub fn finish(&mut self, out: &mut Vec<f32>) -> Result<(), String> {
+        self.finish_at(self.now(), out)
+    }
+
+    /// Samples the inputs dropped since the last call because their rings
+    /// were full, counted at `MIX_SAMPLE_RATE`.
+    pub fn take_dropped(&mut self) -> u64 {
+        self.microphone.take_dropped() + self.system.take_dropped()
+    }
+
+    /// Time since `align`, as a timeline position.
+    fn now(&self) -> u64 {
+        (self.started.elapsed().as_secs_f64() * MIX_SAMPLE_RATE as f64) as u64
+    }
+
+    fn mix_at(&mut self, now: u64, out: &mut Vec<f32>) -> Result<(), String> {
+        self.microphone.pull(now, self.emitted, self.tolerance)?;
+        self.system.pull(now, self.emitted, self.tolerance)?;
+        let due = now.saturating_sub(self.delay);
+        self.emit(due.saturating_sub(self.emitted) as usize, out)
+    }
+
+    fn finish_at(&mut self, now: u64, out: &mut Vec<f32>) -> Result<(), String> {
+        self.microphone.pull(now, self.emitted, self.tolerance)?;
+        self.system.pull(now, self.emitted, self.tolerance)?;
+        self.microphone.flush()?;
+        self.system.flush()?;
+        let len = self.microphone.pending.len().max(self.system.pending.len());
+        self.emit(len, out)?;
+        match self.channels_copy.take() {
+            Some(writer) => writer
+                .finalize()
+                .map_err(|e| format!("failed to finalize wav: {e}")),
+            None => Ok(()),
+        }
+    }
+
+    fn emit(&mut self, len: usize, out: &mut Vec<f32>) -> Result<(), String> {
+        out.reserve(len);
+        let mut copy_result = Ok(());
+        for _ in 0..len {
+            let microphone = self.microphone.next();
+            let system = self.system.next();
+            out.push(microphone + system);
+            if let Some(writer) = self.channels_copy.as_mut() {
+                if copy_result.is_ok() {
+                    copy_result = write_frame(writer, microphone, system);
+                }
+            }
+        }
+        self.emitted += len as u64;
+        // A failed copy must not hold up the recording itself.
+        copy_result.inspect_err(|_| self.channels_copy = None)
+    }
+}
+
+fn write_frame(writer: &mut ChannelsCopy, left: f32, right: f32) -> Result<(), String> {
+    for sample in [left, right] {
+        writer
+            .write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+            .map_err(|e| format!("failed to write separate channels: {e}"))?;
+    }
+    Ok(())
+}
+
+/// One source: its ring, conversion to the mix rate and samples not yet
+/// mixed. The first pending sample always belongs at the mixer's `emitted`
+/// position.
+struct Lane {
+    ring: RingReader,
+    sample_rate: u32,
+    /// Dropped samples already reported, at `MIX_SAMPLE_RATE`.
+    reported_dropped: u64,
+    resampler: Option<Resampler>,
+    gain: f32,
+    pending: VecDeque<f32>,
+    read: Vec<f32>,
+    resampled: Vec<f32>,
+}
+
+impl Lane {
+    fn new(cfg: &config::RecordingConfig, (ring, sample_rate): (RingReader, u32), gain_db: f32) -> Result<Self, String> {
+        let resampler = if sample_rate == MIX_SAMPLE_RATE {
+            None
+        } else {
+            Some(Resampler::new(cfg.resample_quality, sample_rate, MIX_SAMPLE_RATE)?)
+        };
+        Ok(Self {
+            ring,
+            sample_rate,
+            reported_dropped: 0,
+            resampler,
+            gain: 10f32.powf(gain_db / 20.0),
+            pending: VecDeque::new(),
+            read: Vec::new(),
+            resampled: Vec::new(),
+        })
+    }
+
+    fn discard(&mut self) {
+        self.read.clear();
+        self.ring.read(&mut self.read);
+        self.read.clear();
+        self.pending.clear();
+    }
+
+    /// Takes what arrived by `now`, taking it to have been captured just
+    /// before. More than `tolerance` away from where the lane's audio ends,
+    /// it is moved there: after silence, or ahead of the lane's slower clock.
+    fn pull(&mut self, now: u64, emitted: u64, tolerance: u64) -> Result<(), String> {
+        self.read.clear();
+        self.ring.read(&mut self.read);
+        let arrived = match self.resampler.as_mut() {
+            Some(resampler) => {
+                self.resampled.clear();
+                resampler.process(&self.read, &mut self.resampled)?;
+                &self.resampled
+            }
+            None => &self.read,
+        };
+        if arrived.is_empty() {
+            return Ok(());
+        }
+        let end = emitted + self.pending.len() as u64;
+        let start = now.saturating_sub(arrived.len() as u64).max(emitted);
+        let mut skip = 0;
+        if start > end + tolerance {
+            self.pending.extend(std::iter::repeat_n(0.0, (start - end) as usize));
+        } else if end > start + tolerance {
+            skip = ((end - start) as usize).min(arrived.len());
+        }
+        self.pending.extend(&arrived[skip..]);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), String> {
+        if let Some(resampler) = self.resampler.as_mut() {
+            self.resampled.clear();
+            resampler.finish(&mut self.resampled)?;
+            self.pending.extend(&self.resampled);
+        }
+        Ok(())
+    }
+
+    fn next(&mut self) -> f32 {
+        self.pending.pop_front().unwrap_or(0.0) * self.gain
+    }
+
+    fn take_dropped(&mut self) -> u64 {
+        let dropped = self.ring.dropped() * MIX_SAMPLE_RATE as u64 / self.sample_rate as u64;
+        let new = dropped - self.reported_dropped;
+        self.reported_dropped = dropped;
+        new
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::ring;
+
+    /// 10 ms at the mix rate, how often the meeting source mixes.
+    const TICK: u64 = MIX_SAMPLE_RATE as u64 / 100;
+
+    fn lane_ring(sample_rate: u32) -> (ring::RingWriter, (RingReader, u32)) {
+        let (writer, reader) = ring::ring(MIX_SAMPLE_RATE as usize);
+        (writer, (reader, sample_rate))
+    }
+
+    #[test]
+    fn mixes_sources_delivered_at_different_rates() {
+        let cfg = config::RecordingConfig {
+            resample_quality: config::ResampleQuality::Linear,
+            ..Default::default()
+        };
+        let (mut microphone, microphone_ring) = lane_ring(MIX_SAMPLE_RATE);
+        let (mut system, system_ring) = lane_ring(16_000);
+        let mut mixer = Mixer::new(&cfg, microphone_ring, system_ring, None).unwrap();
+
+        microphone.push(&[1.0; 100]);
+        system.push(&[1.0; 10]);
+        mixer.align();
+
+        microphone.push(&[0.25; 4_800]);
+        system.push(&[0.5; 1_600]);
+        let mut out = Vec::new();
+        mixer.mix_at(4_800, &mut out).unwrap();
+        assert_eq!(out.len() as u64, 4_800 - mixer.delay);
+        assert!(out[10..].iter().all(|s| (s - 0.75).abs() < 1e-4));
+
+        mixer.finish_at(4_800, &mut out).unwrap();
+        assert!(out.len() > 4_700 && out.len() <= 4_800, "{}", out.len());
+    }
+
+    #[test]
+    fn pads_a_silent_source() {
+        let cfg = config::RecordingConfig {
+            meeting: config::MeetingConfig {
+                microphone_gain_db: -6.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (mut microphone, microphone_ring) = lane_ring(MIX_SAMPLE_RATE);
+        let (_system, system_ring) = lane_ring(MIX_SAMPLE_RATE);
+        let path = std::env::temp_dir().join(format!("voicedictation-mix-test-{}.wav", std::process::id()));
+        let mut mixer = Mixer::new(&cfg, microphone_ring, system_ring, Some(&path)).unwrap();
+
+        microphone.push(&[1.0; 4_800]);
+        let mut out = Vec::new();
+        mixer.mix_at(mixer.delay, &mut out).unwrap();
+        assert!(out.is_empty());
+        mixer.mix_at(4_800, &mut out).unwrap();
+        assert_eq!(out.len() as u64, 4_800 - mixer.delay);
+        assert!((out[0] - 0.501).abs() < 1e-3, "{}", out[0]);
+
+        mixer.finish_at(4_800, &mut out).unwrap();
+        assert_eq!(out.len(), 4_800);
+
+        let reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.spec().channels, 2);
+        let samples: Vec<i16> = reader.into_samples().map(Result::unwrap).collect();
+        assert_eq!(samples.len(), 2 * out.len());
+        assert!(samples[0] > 16_000 && samples[1] == 0);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn keeps_sources_in_step_after_silence() {
+        let (mut microphone, microphone_ring) = lane_ring(MIX_SAMPLE_RATE);
+        let (mut system, system_ring) = lane_ring(MIX_SAMPLE_RATE);
+        let mut mixer = Mixer::new(&config::RecordingConfig::default(), microphone_ring, system_ring, None).unwrap();
+
+        // Each tick's audio is marked with the tick; system audio is negated,
+        // so where the two line up they cancel. The system is silent for the
+        // first half second.
+        let mut out = Vec::new();
+        for tick in 1..=150u64 {
+            let mark = tick as f32 / 1_000.0;
+            microphone.push(&[mark; TICK as usize]);
+            if tick > 50 {
+                system.push(&[-mark; TICK as usize]);
+            }
+            mixer.mix_at(tick * TICK, &mut out).unwrap();
+        }
+        assert!((out[0] - 0.001).abs() < 1e-6);
+        let joined = 50 * TICK as usize;
+        assert!(out[joined..].iter().all(|s| s.abs() < 1e-6));
+    }
+
+    #[test]
+    fn drops_the_excess_of_a_fast_clock() {
+        let (mut microphone, microphone_ring) = lane_ring(MIX_SAMPLE_RATE);
+        let (mut system, system_ring) = lane_ring(MIX_SAMPLE_RATE);
+        let mut mixer = Mixer::new(&config::RecordingConfig::default(), microphone_ring, system_ring, None).unwrap();
+
+        // The system device runs 2% fast for ten seconds.
+        let mut out = Vec::new();
+        for tick in 1..=1_000u64 {
+            microphone.push(&[0.25; TICK as usize]);
+            system.push(&[0.5; TICK as usize * 102 / 100]);
+            mixer.mix_at(tick * TICK, &mut out).unwrap();
+        }
+        assert_eq!(out.len() as u64, 1_000 * TICK - mixer.delay);
+        let backlog = mixer.system.pending.len() as u64;
+        assert!(backlog <= mixer.delay + mixer.tolerance + TICK, "{backlog}");
+    }
+
+    #[test]
+    fn reports_what_the_inputs_dropped() {
+        let (mut microphone, microphone_reader) = ring::ring(10);
+        let (mut system, system_reader) = ring::ring(10);
+        let mut mixer = Mixer::new(
+            &config::RecordingConfig::default(),
+            (microphone_reader, MIX_SAMPLE_RATE),
+            (system_reader, 16_000),
+            None,
+        )
+        .unwrap();
+
+        microphone.push(&[0.0; 15]);
+        system.push(&[0.0; 20]);
+        assert_eq!(mixer.take_dropped(), 5 + 30);
+        assert_eq!(mixer.take_dropped(), 0);
+    }
+}
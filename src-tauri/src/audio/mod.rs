@@ -0,0 +1,1210 @@
+mod convert;
+mod denoise;
+mod device;
+mod dsp;
+mod file;
+mod loopback;
+mod mix;
+#[cfg(target_os = "linux")]
+mod monitor;
+mod playback;
+mod resample;
+mod ring;
+mod segment;
+mod source;
+mod stats;
+mod trim;
+mod vad;
+mod warm;
+
+use crate::config;
+use cpal::traits::{DeviceTrait, StreamTrait};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use std::path::PathBuf;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+pub use device::{list_input_devices, list_output_devices, InputDeviceInfo, OutputDeviceInfo};
+pub use file::{decode_file, file_seconds};
+pub use loopback::{list_loopback_devices, LoopbackDeviceInfo};
+pub use playback::play_recording;
+pub use source::effective_source;
+pub use stats::{AudioLevel, InputStats};
+pub use warm::WarmInput;
+
+/// Notifications sent from the recording pipeline while it is running.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordingEvent {
+    /// Speech was followed by `vad.autoStopSilenceMs` of silence.
+    SilenceDetected,
+    /// The configured input device was not found; the default device is used instead.
+    InputDeviceFallback { requested: String, using: String },
+    /// The input stream reported an error; recording continues where possible.
+    StreamError { message: String },
+    /// The input device disappeared and recording moved to another device.
+    InputDeviceSwitched { from: String, to: String },
+    /// The input device disappeared and no replacement was used; the audio
+    /// captured so far is kept.
+    InputDeviceLost,
+    /// Input level, sent about 20 times per second while recording.
+    Level(AudioLevel),
+    /// An in-memory recording hit `memoryCapSeconds`; later audio is dropped.
+    MemoryCapReached,
+    /// The memory cap will be reached in `seconds_left` (`limitWarningSeconds`).
+    LimitApproaching { seconds_left: u64 },
+    /// The recording failed; `stats` covers the input up to the failure.
+    Failed { stats: InputStats },
+}
+
+/// Where the finished 16 kHz WAV goes.
+pub enum RecordingTarget {
+    File(PathBuf),
+    /// Keep the encoded WAV in memory so nothing is written to disk.
+    Memory,
+}
+
+impl RecordingTarget {
+    /// Where segment `index` (from 1) of a rolled-over recording goes: later
+    /// segments of a file get numbered siblings (`name-2.wav`).
+    fn segment(&self, index: u32) -> Self {
+        match self {
+            Self::File(path) if index == 1 => Self::File(path.clone()),
+            Self::File(path) => {
+                let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("recording");
+                Self::File(path.with_file_name(format!("{stem}-{index}.wav")))
+            }
+            Self::Memory => Self::Memory,
+        }
+    }
+}
+
+/// A finished recording, as 16 kHz mono 16-bit WAV.
+#[derive(Debug)]
+pub enum RecordedAudio {
+    File(PathBuf),
+    Memory(Vec<u8>),
+}
+
+/// What `RecordingHandle::stop` hands back.
+#[derive(Debug)]
+pub struct FinishedRecording {
+    pub audio: RecordedAudio,
+    pub stats: InputStats,
+}
+
+impl RecordedAudio {
+    pub fn wav_bytes(&self) -> Result<Vec<u8>, String> {
+        match self {
+            Self::File(path) => std::fs::read(path).map_err(|e| format!("failed to read wav {}: {e}", path.display())),
+            Self::Memory(bytes) => Ok(bytes.clone()),
+        }
+    }
+
+    /// Deletes the backing file, if there is one.
+    pub fn discard(&self) {
+        if let Self::File(path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+pub struct RecordingOptions {
+    pub config: config::RecordingConfig,
+    pub events: Sender<RecordingEvent>,
+    /// Also write the capture before noise suppression and DSP here, for A/B comparisons.
+    pub raw_copy_path: Option<PathBuf>,
+    /// In `CaptureMode::Meeting`, also write the input device and system
+    /// audio here as the left and right channels of a WAV.
+    pub channels_copy_path: Option<PathBuf>,
+    /// Idle stream to take the recording from, starting with its pre-roll.
+    pub warm_input: Option<WarmInput>,
+    /// Receives each finished segment when `recording.rollover` or
+    /// `recording.incremental` is enabled; without it the recording is never split.
+    pub segments: Option<Sender<FinishedRecording>>,
+}
+
+pub struct RecordingHandle {
+    stop: Sender<Duration>,
+    /// Shared by every input's ring.
+    pause: ring::Pause,
+    join: Option<JoinHandle<Result<FinishedRecording, String>>>,
+}
+
+impl RecordingHandle {
+    /// Suspends or resumes capture. Audio captured while paused is discarded
+    /// rather than written, even if the writer has yet to catch up.
+    pub fn set_paused(&self, paused: bool) {
+        self.pause.set(paused);
+    }
+
+    /// Stops capture and finalizes the recording. With a non-zero `tail`,
+    /// capture continues for up to that long first (see `recording.tail`).
+    pub fn stop(mut self, tail: Duration) -> Result<FinishedRecording, String> {
+        let _ = self.stop.send(tail);
+        let join = self
+            .join
+            .take()
+            .ok_or_else(|| "recording join handle missing".to_string())?;
+        join.join()
+            .map_err(|_| "recording thread panicked".to_string())?
+    }
+}
+
+pub fn start_recording(target: RecordingTarget, mut options: RecordingOptions) -> Result<RecordingHandle, String> {
+    let (stop_tx, stop_rx) = crossbeam_channel::bounded::<Duration>(1);
+    let (tx, rx) = crossbeam_channel::unbounded::<Capture>();
+    let pause = ring::Pause::default();
+    let input_pause = pause.clone();
+
+    let join = std::thread::spawn(move || {
+        let cfg = options.config.clone();
+        let events = options.events.clone();
+
+        let (err_tx, err_rx) = crossbeam_channel::unbounded::<(u32, cpal::StreamError)>();
+        let ring_capacity = (cfg.capture_buffer_kb.max(1) * 1_024) as usize / std::mem::size_of::<f32>();
+        let sink = |generation| source::SourceSink {
+            tx: tx.clone(),
+            errors: err_tx.clone(),
+            generation,
+            ring_capacity,
+            pause: input_pause.clone(),
+        };
+        let mut generation = 0;
+        let mut source = source::open_source(
+            &cfg,
+            options.warm_input.take(),
+            options.channels_copy_path.take(),
+            events.clone(),
+        )?;
+        let opened = source.open(sink(generation))?;
+        let mut device_name = opened.name.clone();
+        let mut input = Some(opened);
+        let in_sample_rate = input.as_ref().map_or(0, |i| i.sample_rate);
+        let writer = std::thread::spawn(move || writer_thread(rx, target, in_sample_rate, options));
+
+        loop {
+            crossbeam_channel::select! {
+                recv(stop_rx) -> tail => {
+                    let tail = tail.unwrap_or_default();
+                    if input.is_some() && !tail.is_zero() {
+                        if cfg.tail.end_on_silence {
+                            let (done_tx, done_rx) = crossbeam_channel::bounded(1);
+                            let _ = tx.send(Capture::TailStarted(done_tx));
+                            let _ = done_rx.recv_timeout(tail);
+                        } else {
+                            std::thread::sleep(tail);
+                        }
+                    }
+                    break;
+                }
+                recv(err_rx) -> err => {
+                    let Ok((stream_generation, err)) = err else {
+                        break;
+                    };
+                    if stream_generation != generation {
+                        continue;
+                    }
+                    let lost = matches!(err, cpal::StreamError::DeviceNotAvailable);
+                    let message = describe_stream_error(&device_name, &err);
+                    let _ = events.send(RecordingEvent::StreamError { message });
+                    if !lost {
+                        continue;
+                    }
+
+                    // Stop the dead stream; the audio captured so far stays in the pipeline.
+                    // The replacement brings its own ring, read once the old one is drained.
+                    drop(input.take());
+                    generation += 1;
+                    match source.replace(&device_name, sink(generation)) {
+                        Some(opened) => {
+                            let name = opened.name.clone();
+                            let _ = events.send(RecordingEvent::InputDeviceSwitched {
+                                from: std::mem::replace(&mut device_name, name.clone()),
+                                to: name,
+                            });
+                            input = Some(opened);
+                        }
+                        None => {
+                            let _ = events.send(RecordingEvent::InputDeviceLost);
+                        }
+                    }
+                }
+            }
+        }
+
+        drop(input);
+        drop(tx);
+
+        writer
+            .join()
+            .map_err(|_| "writer thread panicked".to_string())?
+    });
+
+    Ok(RecordingHandle {
+        stop: stop_tx,
+        pause,
+        join: Some(join),
+    })
+}
+
+/// Audio and control messages for the writer thread.
+enum Capture {
+    /// Samples from outside the audio thread, such as the pre-roll or a decoded file.
+    Samples(Vec<f32>),
+    /// Buffers after this one arrive at a new device rate.
+    RateChanged(u32),
+    /// A newly opened input. Once everything queued before it has been
+    /// processed, audio is read from `ring` at `sample_rate`.
+    Input { ring: ring::RingReader, sample_rate: u32 },
+    /// Capture was asked to stop; signal once the speaker has gone quiet.
+    TailStarted(Sender<()>),
+}
+
+/// A playing input: a stream of its own, a warm input it is attached to, or a
+/// synthetic source.
+struct OpenInput {
+    name: String,
+    _source: InputSource,
+    sample_rate: u32,
+}
+
+enum InputSource {
+    Stream { _stream: cpal::Stream },
+    Warm { _attachment: warm::Attachment },
+    Playback { _playback: source::Playback },
+    #[cfg(target_os = "linux")]
+    Monitor { _monitor: monitor::MonitorCapture },
+    /// Inputs stop before the mixer, which then mixes what they left behind.
+    Mixed { _inputs: Vec<OpenInput>, _mixer: source::Playback },
+}
+
+fn open_input(
+    device: &cpal::Device,
+    name: String,
+    cfg: &config::RecordingConfig,
+    sink: source::SourceSink,
+) -> Result<OpenInput, String> {
+    let default_config = device
+        .default_input_config()
+        .map_err(|e| format!("failed to get default input config: {e}"))?;
+    open_stream(device, default_config, name, cfg, sink)
+}
+
+/// Opens and plays an input stream on `device` with `stream_config`.
+fn open_stream(
+    device: &cpal::Device,
+    stream_config: cpal::SupportedStreamConfig,
+    name: String,
+    cfg: &config::RecordingConfig,
+    sink: source::SourceSink,
+) -> Result<OpenInput, String> {
+    let (mut writer, reader) = sink.ring();
+    let errors = sink.errors.clone();
+    let generation = sink.generation;
+    // Errors are tagged so ones from a stream that was already replaced are ignored.
+    let (stream, sample_rate) = build_input_with(
+        device,
+        stream_config,
+        cfg,
+        move |mono| writer.push(mono),
+        move |err| {
+            let _ = errors.send((generation, err));
+        },
+    )?;
+    // The recording gets the ring together with its rate before the stream
+    // plays, so none of this device's buffers are read at the previous rate.
+    sink.start(reader, sample_rate);
+    stream
+        .play()
+        .map_err(|e| format!("failed to start input stream: {e}"))?;
+    Ok(OpenInput {
+        name,
+        _source: InputSource::Stream { _stream: stream },
+        sample_rate,
+    })
+}
+
+/// Builds a paused input stream on the device's default config that hands each
+/// callback buffer, downmixed to mono, to `sink`. Returns the stream and its rate.
+fn build_input(
+    device: &cpal::Device,
+    cfg: &config::RecordingConfig,
+    sink: impl FnMut(&[f32]) + Send + 'static,
+    err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
+) -> Result<(cpal::Stream, u32), String> {
+    let default_config = device
+        .default_input_config()
+        .map_err(|e| format!("failed to get default input config: {e}"))?;
+    build_input_with(device, default_config, cfg, sink, err_fn)
+}
+
+/// `build_input` with an explicit stream config, such as an output device's
+/// config for loopback capture.
+fn build_input_with(
+    device: &cpal::Device,
+    default_config: cpal::SupportedStreamConfig,
+    cfg: &config::RecordingConfig,
+    sink: impl FnMut(&[f32]) + Send + 'static,
+    err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
+) -> Result<(cpal::Stream, u32), String> {
+    let sample_rate = default_config.sample_rate().0;
+    let channels = default_config.channels() as usize;
+    let downmixer = convert::Downmixer::new(cfg.channel_mode, cfg.channel_index, channels);
+
+    let sample_format = default_config.sample_format();
+    let config: cpal::StreamConfig = default_config.into();
+    let stream = match sample_format {
+        cpal::SampleFormat::I8 => build_stream::<i8>(device, &config, downmixer, sink, err_fn)?,
+        cpal::SampleFormat::I16 => build_stream::<i16>(device, &config, downmixer, sink, err_fn)?,
+        cpal::SampleFormat::I32 => build_stream::<i32>(device, &config, downmixer, sink, err_fn)?,
+        cpal::SampleFormat::I64 => build_stream::<i64>(device, &config, downmixer, sink, err_fn)?,
+        cpal::SampleFormat::U8 => build_stream::<u8>(device, &config, downmixer, sink, err_fn)?,
+        cpal::SampleFormat::U16 => build_stream::<u16>(device, &config, downmixer, sink, err_fn)?,
+        cpal::SampleFormat::U32 => build_stream::<u32>(device, &config, downmixer, sink, err_fn)?,
+        cpal::SampleFormat::U64 => build_stream::<u64>(device, &config, downmixer, sink, err_fn)?,
+        cpal::SampleFormat::F32 => build_stream::<f32>(device, &config, downmixer, sink, err_fn)?,
+        cpal::SampleFormat::F64 => build_stream::<f64>(device, &config, downmixer, sink, err_fn)?,
+        other => return Err(format!("unsupported sample format: {other:?}")),
+    };
+    Ok((stream, sample_rate))
+}
+
+fn describe_stream_error(device_name: &str, err: &cpal::StreamError) -> String {
+    match err {
+        cpal::StreamError::DeviceNotAvailable => {
+            format!("input device \"{device_name}\" became unavailable during recording")
+        }
+        other => format!("audio input stream error on \"{device_name}\": {other}"),
+    }
+}
+
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mut downmixer: convert::Downmixer,
+    mut sink: impl FnMut(&[f32]) + Send + 'static,
+    err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
+) -> Result<cpal::Stream, String>
+where
+    T: cpal::SizedSample,
+    f32: cpal::FromSample<T>,
+{
+    // Sized for a second of audio so the callback does not allocate.
+    let mut mono = Vec::with_capacity(config.sample_rate.0 as usize);
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _| {
+                mono.clear();
+                downmixer.process(data, convert::sample_to_f32, &mut mono);
+                sink(&mono);
+            },
+            err_fn,
+            None,
+        )
+        .map_err(|e| format!("failed to build input stream: {e}"))
+}
+
+const OUTPUT_SAMPLE_RATE: u32 = 16_000;
+/// Size of one second of the WAVs recordings produce (16 kHz, 16-bit mono).
+pub const WAV_BYTES_PER_SECOND: u64 = OUTPUT_SAMPLE_RATE as u64 * 2;
+
+fn writer_thread(
+    rx: Receiver<Capture>,
+    target: RecordingTarget,
+    in_sample_rate: u32,
+    options: RecordingOptions,
+) -> Result<FinishedRecording, String> {
+    let sample_limit = match target {
+        RecordingTarget::File(_) => None,
+        RecordingTarget::Memory => {
+            let cap_seconds = options
+                .config
+                .memory_cap_seconds
+                .unwrap_or(options.config.max_seconds)
+                .max(1);
+            Some(cap_seconds * OUTPUT_SAMPLE_RATE as u64)
+        }
+    };
+    run_pipeline(rx, target, in_sample_rate, options, sample_limit)
+}
+
+/// Runs capture through the pipeline into `target`. With rollover or
+/// incremental insertion, each finished segment goes to `options.segments`
+/// and capture continues into a new one; the last segment is returned.
+fn run_pipeline(
+    rx: Receiver<Capture>,
+    first: RecordingTarget,
+    in_sample_rate: u32,
+    options: RecordingOptions,
+    sample_limit: Option<u64>,
+) -> Result<FinishedRecording, String> {
+    let cfg = options.config;
+    let events = options.events;
+    let segments = options
+        .segments
+        .filter(|_| cfg.rollover.enabled || cfg.incremental.enabled);
+    // A segment never outgrows `maxSeconds`, so a memory cap at least that
+    // long must not cut it off first.
+    let segment_samples = cfg.max_seconds.max(1) * OUTPUT_SAMPLE_RATE as u64;
+    let sample_limit = sample_limit.filter(|&limit| segments.is_none() || limit < segment_samples);
+
+    let raw_copy = match options.raw_copy_path {
+        Some(path) => Some(RawCopy::new(&path, cfg.resample_quality, in_sample_rate)?),
+        None => None,
+    };
+    let mut input = InputStage {
+        stats: stats::StatsCollector::new(in_sample_rate),
+        meter: stats::LevelMeter::new(in_sample_rate),
+        raw_copy,
+        converter: RateConverter::new(&cfg, in_sample_rate)?,
+        sample_rate: in_sample_rate,
+        events: events.clone(),
+    };
+    let result = run_segments(&rx, first, &cfg, segments.as_ref(), sample_limit, &mut input);
+    if result.is_err() {
+        let _ = events.send(RecordingEvent::Failed { stats: input.take_stats() });
+    }
+    result
+}
+
+/// The segment loop of `run_pipeline`.
+fn run_segments(
+    rx: &Receiver<Capture>,
+    first: RecordingTarget,
+    cfg: &config::RecordingConfig,
+    segments: Option<&Sender<FinishedRecording>>,
+    sample_limit: Option<u64>,
+    input: &mut InputStage,
+) -> Result<FinishedRecording, String> {
+    let events = input.events.clone();
+    let mut ring: Option<ring::RingReader> = None;
+    let mut index = 1;
+    let mut target = first.segment(index);
+    let mut carry = Vec::new();
+    let mut signal = SignalStage::new(cfg, OUTPUT_SAMPLE_RATE);
+    loop {
+        let segmented = segments.is_some();
+        let (audio, end) = match &target {
+            RecordingTarget::File(path) => {
+                let output = OutputStage::new(
+                    create_wav(path)?,
+                    &mut signal,
+                    cfg,
+                    OUTPUT_SAMPLE_RATE,
+                    sample_limit,
+                    events.clone(),
+                );
+                let end = run_segment(rx, cfg, input, &mut ring, output.segmented(cfg, segmented), carry)?;
+                (RecordedAudio::File(path.clone()), end)
+            }
+            RecordingTarget::Memory => {
+                let mut buffer = std::io::Cursor::new(Vec::new());
+                let writer = hound::WavWriter::new(&mut buffer, wav_spec())
+                    .map_err(|e| format!("failed to create in-memory wav: {e}"))?;
+                let output = OutputStage::new(writer, &mut signal, cfg, OUTPUT_SAMPLE_RATE, sample_limit, events.clone());
+                let end = run_segment(rx, cfg, input, &mut ring, output.segmented(cfg, segmented), carry)?;
+                (RecordedAudio::Memory(buffer.into_inner()), end)
+            }
+        };
+        let stats = input.take_stats();
+        let finished = FinishedRecording { audio, stats };
+        match (end, segments) {
+            (SegmentEnd::Next(rest), Some(segments)) => {
+                let _ = segments.send(finished);
+                index += 1;
+                target = first.segment(index);
+                carry = rest;
+            }
+            _ => {
+                if let Some(raw_copy) = input.raw_copy.take() {
+                    raw_copy.finish()?;
+                }
+                return Ok(finished);
+            }
+        }
+    }
+}
+
+/// Why a segment ended.
+enum SegmentEnd {
+    /// Capture has ended.
+    Finished,
+    /// The segment ended early; capture continues in the next one, starting
+    /// with the 16 kHz samples that did not fit.
+    Next(Vec<f32>),
+}
+
+/// Feeds capture into one segment until it is full or capture ends.
+fn run_segment<W: std::io::Write + std::io::Seek>(
+    rx: &Receiver<Capture>,
+    cfg: &config::RecordingConfig,
+    input: &mut InputStage,
+    ring: &mut Option<ring::RingReader>,
+    mut output: OutputStage<'_, W>,
+    mut carry: Vec<f32>,
+) -> Result<SegmentEnd, String> {
+    let mut chunk = Vec::new();
+    let mut out = Vec::new();
+    output.push(&mut carry)?;
+    loop {
+        // A recording winding down after a stop request is not split.
+        if output.tail.is_none() && output.segmenter.as_ref().is_some_and(|s| s.is_due()) {
+            let rest = std::mem::take(&mut output.overflow);
+            output.finish()?;
+            return Ok(SegmentEnd::Next(rest));
+        }
+        let capture = match rx.recv_timeout(RING_POLL_INTERVAL) {
+            Ok(capture) => Some(capture),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        out.clear();
+        // Whatever the current input buffered so far precedes the message.
+        if let Some(ring) = ring.as_mut() {
+            chunk.clear();
+            ring.read(&mut chunk);
+            input.push(&chunk, &mut out)?;
+        }
+        match capture {
+            Some(Capture::Samples(samples)) => input.push(&samples, &mut out)?,
+            Some(Capture::RateChanged(rate)) => input.set_rate(cfg, rate, &mut out)?,
+            Some(Capture::Input { ring: next, sample_rate }) => {
+                if let Some(previous) = ring.replace(next) {
+                    input.finish_ring(previous, &mut chunk, &mut out)?;
+                }
+                if sample_rate != input.sample_rate {
+                    input.set_rate(cfg, sample_rate, &mut out)?;
+                }
+            }
+            Some(Capture::TailStarted(done)) => {
+                output.tail = Some((
+                    vad::Vad::new(OUTPUT_SAMPLE_RATE, cfg.vad.threshold_db),
+                    vad::EndpointDetector::armed(cfg.tail.silence_ms),
+                    done,
+                ));
+            }
+            None => {}
+        }
+        output.push(&mut out)?;
+    }
+
+    out.clear();
+    if let Some(previous) = ring.take() {
+        input.finish_ring(previous, &mut chunk, &mut out)?;
+    }
+    input.converter.finish(&mut out)?;
+    output.push(&mut out)?;
+    output.finish()?;
+    Ok(SegmentEnd::Finished)
+}
+
+/// How often the writer checks the current input's ring for new audio.
+const RING_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Device-rate side of the pipeline: statistics, level meter, optional raw
+/// copy, then conversion to 16 kHz.
+struct InputStage {
+    stats: stats::StatsCollector,
+    meter: stats::LevelMeter,
+    raw_copy: Option<RawCopy>,
+    converter: RateConverter,
+    sample_rate: u32,
+    events: Sender<RecordingEvent>,
+}
+
+impl InputStage {
+    /// The statistics so far, starting a fresh count.
+    fn take_stats(&mut self) -> InputStats {
+        std::mem::replace(&mut self.stats, stats::StatsCollector::new(self.sample_rate)).finish()
+    }
+
+    fn push(&mut self, samples: &[f32], out: &mut Vec<f32>) -> Result<(), String> {
+        if samples.is_empty() {
+            return Ok(());
+        }
+        self.stats.push(samples);
+        if let Some(level) = self.meter.push(samples) {
+            let _ = self.events.send(RecordingEvent::Level(level));
+        }
+        if let Some(raw_copy) = self.raw_copy.as_mut() {
+            raw_copy.push(samples)?;
+        }
+        self.converter.process(samples, out)
+    }
+
+    fn set_rate(&mut self, cfg: &config::RecordingConfig, rate: u32, out: &mut Vec<f32>) -> Result<(), String> {
+        self.converter.finish(out)?;
+        self.converter = RateConverter::new(cfg, rate)?;
+        self.sample_rate = rate;
+        self.stats.set_rate(rate);
+        self.meter.set_rate(rate);
+        if let Some(raw_copy) = self.raw_copy.as_mut() {
+            raw_copy.set_rate(cfg.resample_quality, rate)?;
+        }
+        Ok(())
+    }
+
+    /// Processes what is left in a ring whose input has closed.
+    fn finish_ring(&mut self, mut ring: ring::RingReader, chunk: &mut Vec<f32>, out: &mut Vec<f32>) -> Result<(), String> {
+        chunk.clear();
+        ring.read(chunk);
+        self.push(chunk, out)?;
+        self.stats.add_dropped(ring.dropped(), self.sample_rate);
+        Ok(())
+    }
+}
+
+/// Device-rate mono to 16 kHz: optional noise suppression followed by resampling.
+struct RateConverter {
+    denoise: Option<denoise::DenoiseStage>,
+    resampler: resample::Resampler,
+    denoised: Vec<f32>,
+}
+
+impl RateConverter {
+    fn new(cfg: &config::RecordingConfig, in_sample_rate: u32) -> Result<Self, String> {
+        let denoise = if cfg.denoise {
+            Some(denoise::DenoiseStage::new(cfg.resample_quality, in_sample_rate)?)
+        } else {
+            None
+        };
+        let resampler_in_rate = if denoise.is_some() {
+            denoise::DENOISE_SAMPLE_RATE
+        } else {
+            in_sample_rate
+        };
+        Ok(Self {
+            denoise,
+            resampler: resample::Resampler::new(cfg.resample_quality, resampler_in_rate, OUTPUT_SAMPLE_RATE)?,
+            denoised: Vec::new(),
+        })
+    }
+
+    fn process(&mut self, input: &[f32], out: &mut Vec<f32>) -> Result<(), String> {
+        match self.denoise.as_mut() {
+            Some(denoise) => {
+                self.denoised.clear();
+                denoise.process(input, &mut self.denoised)?;
+                self.resampler.process(&self.denoised, out)
+            }
+            None => self.resampler.process(input, out),
+        }
+    }
+
+    fn finish(&mut self, out: &mut Vec<f32>) -> Result<(), String> {
+        if let Some(denoise) = self.denoise.as_mut() {
+            self.denoised.clear();
+            denoise.finish(&mut self.denoised)?;
+            self.resampler.process(&self.denoised, out)?;
+        }
+        self.resampler.finish(out)
+    }
+}
+
+fn wav_spec() -> hound::WavSpec {
+    hound::WavSpec {
+        channels: 1,
+        sample_rate: OUTPUT_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    }
+}
+
+fn create_wav(path: &std::path::Path) -> Result<hound::WavWriter<std::io::BufWriter<std::fs::File>>, String> {
+    hound::WavWriter::create(path, wav_spec()).map_err(|e| format!("failed to create wav {}: {e}", path.display()))
+}
+
+/// Unprocessed 16 kHz copy of the capture.
+struct RawCopy {
+    writer: hound::WavWriter<std::io::BufWriter<std::fs::File>>,
+    resampler: resample::Resampler,
+    out: Vec<f32>,
+}
+
+impl RawCopy {
+    fn new(path: &std::path::Path, quality: config::ResampleQuality, in_sample_rate: u32) -> Result<Self, String> {
+        Ok(Self {
+            writer: create_wav(path)?,
+            resampler: resample::Resampler::new(quality, in_sample_rate, OUTPUT_SAMPLE_RATE)?,
+            out: Vec::new(),
+        })
+    }
+
+    fn push(&mut self, samples: &[f32]) -> Result<(), String> {
+        self.out.clear();
+        self.resampler.process(samples, &mut self.out)?;
+        write_samples(&mut self.writer, &self.out)
+    }
+
+    fn set_rate(&mut self, quality: config::ResampleQuality, in_sample_rate: u32) -> Result<(), String> {
+        self.flush()?;
+        self.resampler = resample::Resampler::new(quality, in_sample_rate, OUTPUT_SAMPLE_RATE)?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), String> {
+        self.out.clear();
+        self.resampler.finish(&mut self.out)?;
+        write_samples(&mut self.writer, &self.out)
+    }
+
+    fn finish(mut self) -> Result<(), String> {
+        self.flush()?;
+        self.writer
+            .finalize()
+            .map_err(|e| format!("failed to finalize wav: {e}"))
+    }
+}
+
+/// The part of the 16 kHz processing that carries on from one segment to
+/// the next: the DSP chain's filter and gain, and auto-stop, which must hear
+/// a pause that spans a segment boundary as one pause.
+struct SignalStage {
+    dsp: dsp::DspChain,
+    auto_stop: Option<(vad::Vad, vad::EndpointDetector)>,
+}
+
+impl SignalStage {
+    fn new(cfg: &config::RecordingConfig, sample_rate: u32) -> Self {
+        Self {
+            dsp: dsp::DspChain::new(&cfg.dsp, cfg.vad.threshold_db, sample_rate),
+            auto_stop: cfg.vad.auto_stop.then(|| {
+                (
+                    vad::Vad::new(sample_rate, cfg.vad.threshold_db),
+                    vad::EndpointDetector::new(cfg.vad.auto_stop_silence_ms),
+                )
+            }),
+        }
+    }
+}
+
+/// Everything that happens to the 16 kHz signal of one segment before it
+/// lands in the WAV.
+struct OutputStage<'a, W: std::io::Write + std::io::Seek> {
+    writer: hound::WavWriter<W>,
+    signal: &'a mut SignalStage,
+    trimmer: Option<trim::SilenceTrimmer>,
+    /// Watches the tail after a stop request for silence.
+    tail: Option<(vad::Vad, vad::EndpointDetector, Sender<()>)>,
+    /// Samples still accepted before the memory cap is reached.
+    remaining: Option<u64>,
+    /// `remaining` at which `LimitApproaching` is sent.
+    warn_at: Option<u64>,
+    /// Ends the segment for rollover or incremental insertion.
+    segmenter: Option<segment::Segmenter>,
+    /// Samples past the end of the segment, for the next one.
+    overflow: Vec<f32>,
+    events: Sender<RecordingEvent>,
+}
+
+impl<'a, W: std::io::Write + std::io::Seek> OutputStage<'a, W> {
+    fn new(
+        writer: hound::WavWriter<W>,
+        signal: &'a mut SignalStage,
+        cfg: &config::RecordingConfig,
+        sample_rate: u32,
+        sample_limit: Option<u64>,
+        events: Sender<RecordingEvent>,
+    ) -> Self {
+        // A cap no shorter than `maxSeconds` is only reached where the time
+        // limit stops the recording, and that already warns.
+        let warns_of_cap = sample_limit.is_some_and(|limit| limit < cfg.max_seconds.max(1) * sample_rate as u64);
+        let trimmer = cfg
+            .trim
+            .enabled
+            .then(|| trim::SilenceTrimmer::new(sample_rate, cfg.vad.threshold_db, cfg.trim.padding_ms));
+        Self {
+            writer,
+            signal,
+            trimmer,
+            tail: None,
+            remaining: sample_limit,
+            warn_at: (cfg.limit_warning_seconds > 0 && warns_of_cap).then(|| cfg.limit_warning_seconds * sample_rate as u64),
+            segmenter: None,
+            overflow: Vec::new(),
+            events,
+        }
+    }
+
+    /// Ends the segment as `recording.rollover` and `recording.incremental`
+    /// ask when `enabled`.
+    fn segmented(mut self, cfg: &config::RecordingConfig, enabled: bool) -> Self {
+        if enabled {
+            self.segmenter = segment::Segmenter::for_config(cfg, OUTPUT_SAMPLE_RATE);
+        }
+        self
+    }
+
+    fn push(&mut self, samples: &mut [f32]) -> Result<(), String> {
+        let room = self.segmenter.as_ref().filter(|_| self.tail.is_none()).map(|s| s.room());
+        let samples = match room {
+            Some(room) if room < samples.len() as u64 => {
+                let (now, later) = samples.split_at_mut(room as usize);
+                self.overflow.extend_from_slice(later);
+                now
+            }
+            _ => samples,
+        };
+        let samples = match self.remaining.as_mut() {
+            Some(remaining) => {
+                if *remaining == 0 {
+                    return Ok(());
+                }
+                let keep = samples.len().min(*remaining as usize);
+                *remaining -= keep as u64;
+                if self.warn_at.is_some_and(|warn_at| *remaining <= warn_at && *remaining > 0) {
+                    self.warn_at = None;
+                    let seconds_left = remaining.div_ceil(OUTPUT_SAMPLE_RATE as u64);
+                    let _ = self.events.send(RecordingEvent::LimitApproaching { seconds_left });
+                }
+                if *remaining == 0 {
+                    let _ = self.events.send(RecordingEvent::MemoryCapReached);
+                }
+                &mut samples[..keep]
+            }
+            None => samples,
+        };
+        self.signal.dsp.pre_process(samples);
+
+        if let Some((vad, endpoint)) = self.signal.auto_stop.as_mut() {
+            if vad.push(samples).into_iter().any(|speech| endpoint.update(speech)) {
+                let _ = self.events.send(RecordingEvent::SilenceDetected);
+            }
+        }
+
+        if let Some(segmenter) = self.segmenter.as_mut() {
+            segmenter.push(samples);
+        }
+
+        if let Some((vad, endpoint, done)) = self.tail.as_mut() {
+            if vad.push(samples).into_iter().any(|speech| endpoint.update(speech)) {
+                let _ = done.try_send(());
+                self.tail = None;
+            }
+        }
+
+        match self.trimmer.as_mut() {
+            Some(trimmer) => {
+                let mut kept = trimmer.push(samples);
+                self.write(&mut kept)
+            }
+            None => self.write(samples),
+        }
+    }
+
+    fn write(&mut self, samples: &mut [f32]) -> Result<(), String> {
+        self.signal.dsp.post_process(samples);
+        write_samples(&mut self.writer, samples)
+    }
+
+    fn finish(mut self) -> Result<(), String> {
+        if let Some(trimmer) = self.trimmer.take() {
+            self.write(&mut trimmer.finish())?;
+        }
+        self.writer
+            .finalize()
+            .map_err(|e| format!("failed to finalize wav: {e}"))
+    }
+}
+fn write_samples<W: std::io::Write + std::io::Seek>(
+    writer: &mut hound::WavWriter<W>,
+    samples: &[f32],
+) -> Result<(), String> {
+    for &sample in samples {
+        let i16_sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        writer
+            .write_sample(i16_sample)
+            .map_err(|e| format!("failed to write wav sample: {e}"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_memory_recording_is_a_wav_capped_at_the_memory_limit() {
+        let cfg = config::RecordingConfig {
+            max_seconds: 2,
+            memory_cap_seconds: Some(1),
+            trim: config::TrimConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let (events_tx, events_rx) = crossbeam_channel::unbounded();
+        for _ in 0..30 {
+            tx.send(Capture::Samples(vec![0.1; 1_600])).unwrap();
+        }
+        drop(tx);
+
+        let options = RecordingOptions {
+            config: cfg,
+            events: events_tx,
+            raw_copy_path: None,
+            channels_copy_path: None,
+            warm_input: None,
+            segments: None,
+        };
+        let RecordedAudio::Memory(bytes) = writer_thread(rx, RecordingTarget::Memory, 16_000, options).unwrap().audio else {
+            panic!("expected an in-memory recording");
+        };
+
+        let reader = hound::WavReader::new(std::io::Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.spec(), wav_spec());
+        assert_eq!(reader.len(), 16_000);
+        let events: Vec<_> = events_rx
+            .try_iter()
+            .filter(|e| !matches!(e, RecordingEvent::Level(_)))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                RecordingEvent::LimitApproaching { seconds_left: 1 },
+                RecordingEvent::MemoryCapReached
+            ]
+        );
+    }
+
+    #[test]
+    fn memory_cap_at_the_time_limit_leaves_the_warning_to_it() {
+        let cfg = config::RecordingConfig {
+            max_seconds: 1,
+            ..Default::default()
+        };
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let (events_tx, events_rx) = crossbeam_channel::unbounded();
+        tx.send(Capture::Samples(vec![0.1; 32_000])).unwrap();
+        drop(tx);
+
+        let options = RecordingOptions {
+            config: cfg,
+            events: events_tx,
+            raw_copy_path: None,
+            channels_copy_path: None,
+            warm_input: None,
+            segments: None,
+        };
+        writer_thread(rx, RecordingTarget::Memory, 16_000, options).unwrap();
+        let events: Vec<_> = events_rx
+            .try_iter()
+            .filter(|e| !matches!(e, RecordingEvent::Level(_)))
+            .collect();
+        assert_eq!(events, vec![RecordingEvent::MemoryCapReached]);
+    }
+
+    #[test]
+    fn rollover_hands_off_full_segments_and_keeps_recording() {
+        let cfg = config::RecordingConfig {
+            max_seconds: 1,
+            rollover: config::RolloverConfig {
+                enabled: true,
+                search_seconds: 0,
+                ..Default::default()
+            },
+            trim: config::TrimConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        for _ in 0..25 {
+            tx.send(Capture::Samples(vec![0.1; 1_600])).unwrap();
+        }
+        drop(tx);
+
+        let (segments_tx, segments_rx) = crossbeam_channel::unbounded();
+        let (events_tx, events_rx) = crossbeam_channel::unbounded();
+        let options = RecordingOptions {
+            config: cfg,
+            events: events_tx,
+            raw_copy_path: None,
+            channels_copy_path: None,
+            warm_input: None,
+            segments: Some(segments_tx),
+        };
+        let last = writer_thread(rx, RecordingTarget::Memory, 16_000, options).unwrap();
+
+        let wav_len = |audio: &RecordedAudio| {
+            let RecordedAudio::Memory(bytes) = audio else {
+                panic!("expected an in-memory recording");
+            };
+            hound::WavReader::new(std::io::Cursor::new(bytes)).unwrap().len()
+        };
+        let segments: Vec<_> = segments_rx.try_iter().collect();
+        assert_eq!(segments.len(), 2);
+        for segment in &segments {
+            assert_eq!(wav_len(&segment.audio), 16_000);
+        }
+        assert_eq!(wav_len(&last.audio), 8_000);
+        // Input statistics split with the segments, to within one input buffer.
+        let seconds: Vec<f32> = segments.iter().chain([&last]).map(|f| f.stats.seconds).collect();
+        assert!((seconds.iter().sum::<f32>() - 2.5).abs() < 1e-4, "{seconds:?}");
+        assert!(seconds[..2].iter().all(|s| (s - 1.0).abs() <= 0.1 + 1e-4), "{seconds:?}");
+        assert!(!events_rx.try_iter().any(|e| e == RecordingEvent::MemoryCapReached));
+    }
+
+    #[test]
+    fn auto_stop_hears_a_pause_that_ends_an_incremental_segment() {
+        let cfg = config::RecordingConfig {
+            incremental: config::IncrementalConfig {
+                enabled: true,
+                pause_ms: 800,
+            },
+            vad: config::VadConfig {
+                auto_stop: true,
+                auto_stop_silence_ms: 2_000,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // One second of speech, then three of silence: the segment ends
+        // 800 ms into the pause, and auto-stop fires in the next one.
+        let (tx, rx) = crossbeam_channel::unbounded();
+        tx.send(Capture::Samples((0..16_000).map(|i| 0.5 * (i as f32 * 0.2).sin()).collect()))
+            .unwrap();
+        for _ in 0..30 {
+            tx.send(Capture::Samples(vec![0.0; 1_600])).unwrap();
+        }
+        drop(tx);
+
+        let (segments_tx, segments_rx) = crossbeam_channel::unbounded();
+        let (events_tx, events_rx) = crossbeam_channel::unbounded();
+        let options = RecordingOptions {
+            config: cfg,
+            events: events_tx,
+            raw_copy_path: None,
+            channels_copy_path: None,
+            warm_input: None,
+            segments: Some(segments_tx),
+        };
+        writer_thread(rx, RecordingTarget::Memory, 16_000, options).unwrap();
+
+        assert_eq!(segments_rx.try_iter().count(), 1);
+        assert!(events_rx.try_iter().any(|e| e == RecordingEvent::SilenceDetected));
+    }
+
+    #[test]
+    fn full_capture_ring_drops_input_and_reports_it() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let (mut writer, reader) = ring::ring(8_000);
+        tx.send(Capture::Input { ring: reader, sample_rate: 16_000 }).unwrap();
+        // A stalled writer: two seconds arrive while only half a second fits.
+        for _ in 0..20 {
+            writer.push(&[0.1; 1_600]);
+        }
+        drop(writer);
+        drop(tx);
+
+        let (events, _) = crossbeam_channel::unbounded();
+        let options = RecordingOptions {
+            config: config::RecordingConfig::default(),
+            events,
+            raw_copy_path: None,
+            channels_copy_path: None,
+            warm_input: None,
+            segments: None,
+        };
+        let stats = writer_thread(rx, RecordingTarget::Memory, 16_000, options).unwrap().stats;
+        assert!((stats.seconds - 0.5).abs() < 1e-6);
+        assert!((stats.dropped_seconds - 1.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn device_switch_to_another_rate_keeps_earlier_audio_and_timing() {
+        let cfg = config::RecordingConfig {
+            trim: config::TrimConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let (mut first, first_ring) = ring::ring(48_000);
+        first.push(&[0.1; 24_000]);
+        tx.send(Capture::Input { ring: first_ring, sample_rate: 48_000 }).unwrap();
+        // The new device is already delivering by the time its ring is read.
+        let (mut second, second_ring) = ring::ring(44_100);
+        second.push(&[0.1; 22_050]);
+        tx.send(Capture::Input { ring: second_ring, sample_rate: 44_100 }).unwrap();
+        drop(tx);
+
+        let options = RecordingOptions {
+            config: cfg,
+            events: crossbeam_channel::unbounded().0,
+            raw_copy_path: None,
+            channels_copy_path: None,
+            warm_input: None,
+            segments: None,
+        };
+        let RecordedAudio::Memory(bytes) = writer_thread(rx, RecordingTarget::Memory, 48_000, options).unwrap().audio else {
+            panic!("expected an in-memory recording");
+        };
+        let reader = hound::WavReader::new(std::io::Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.len(), 16_000);
+    }
+
+    #[test]
+    fn audio_captured_while_paused_is_discarded() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let pause = ring::Pause::default();
+        let (mut writer, reader) = ring::pausable_ring(16_000, &pause);
+        tx.send(Capture::Input { ring: reader, sample_rate: 16_000 }).unwrap();
+
+        // Pausing applies to what is captured from then on, even though the
+        // writer has not read any of it yet.
+        writer.push(&[0.1; 4_000]);
+        pause.set(true);
+        writer.push(&[0.1; 8_000]);
+        pause.set(false);
+        writer.push(&[0.1; 4_000]);
+        drop(writer);
+        drop(tx);
+
+        let options = RecordingOptions {
+            config: config::RecordingConfig::default(),
+            events: crossbeam_channel::unbounded().0,
+            raw_copy_path: None,
+            channels_copy_path: None,
+            warm_input: None,
+            segments: None,
+        };
+        let stats = writer_thread(rx, RecordingTarget::Memory, 16_000, options).unwrap().stats;
+        assert!((stats.seconds - 0.5).abs() < 1e-6, "{}", stats.seconds);
+    }
+
+    #[test]
+    fn tail_ends_once_the_speaker_goes_quiet() {
+        let speech: Vec<f32> = (0..1_600).map(|i| 0.5 * (i as f32 * 0.2).sin()).collect();
+        let run = |after_stop: Vec<f32>| {
+            let (tx, rx) = crossbeam_channel::unbounded();
+            let (done_tx, done_rx) = crossbeam_channel::bounded(1);
+            tx.send(Capture::Samples(speech.clone())).unwrap();
+            tx.send(Capture::TailStarted(done_tx)).unwrap();
+            tx.send(Capture::Samples(after_stop)).unwrap();
+            drop(tx);
+            let options = RecordingOptions {
+                config: config::RecordingConfig::default(),
+                events: crossbeam_channel::unbounded().0,
+                raw_copy_path: None,
+                channels_copy_path: None,
+                warm_input: None,
+                segments: None,
+            };
+            writer_thread(rx, RecordingTarget::Memory, 16_000, options).unwrap();
+            done_rx.try_recv().is_ok()
+        };
+
+        assert!(run(vec![0.0; 8_000]));
+        assert!(!run(speech.repeat(5)));
+    }
+}
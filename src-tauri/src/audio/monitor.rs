@@ -0,0 +1,196 @@
+use super::convert::Downmixer;
+use super::source::SourceSink;
+use super::{InputSource, OpenInput};
+use crate::config;
+use std::ffi::OsStr;
+use std::io::{ErrorKind, Read};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// Records a PulseAudio source; PipeWire provides it too (`pipewire-pulse`).
+const PAREC: &str = "parec";
+/// What the sound server is asked to deliver; it converts to this.
+const MONITOR_SAMPLE_RATE: u32 = 48_000;
+const MONITOR_CHANNELS: usize = 2;
+/// Read size, like a 20 ms device callback.
+const CHUNK_FRAMES: usize = MONITOR_SAMPLE_RATE as usize / 50;
+const FRAME_BYTES: usize = MONITOR_CHANNELS * std::mem::size_of::<f32>();
+
+/// Whether `parec` is on `path` (a `PATH` value).
+pub fn available(path: Option<&OsStr>) -> bool {
+    path.is_some_and(|path| std::env::split_paths(path).any(|dir| dir.join(PAREC).is_file()))
+}
+
+/// The arguments that make `parec` write `source` to stdout as raw samples.
+fn parec_args(source: &str) -> Vec<String> {
+    vec![
+        format!("--device={source}"),
+        "--raw".to_string(),
+        "--format=float32le".to_string(),
+        format!("--rate={MONITOR_SAMPLE_RATE}"),
+        format!("--channels={MONITOR_CHANNELS}"),
+        "--latency-msec=20".to_string(),
+    ]
+}
+
+/// Starts recording the PulseAudio `source` (e.g. `@DEFAULT_MONITOR@`) into
+/// `sink`. The source is passed to `parec` on its command line, so nothing
+/// else connecting to the sound server is affected.
+pub(super) fn open_monitor(
+    source: &str,
+    name: String,
+    cfg: &config::RecordingConfig,
+    sink: SourceSink,
+) -> Result<OpenInput, String> {
+    let child = Command::new(PAREC)
+        .args(parec_args(source))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => {
+                "monitor capture needs parec (PulseAudio utilities, or pipewire-pulse on PipeWire)".to_string()
+            }
+            _ => format!("failed to run {PAREC}: {e}"),
+        })?;
+    start(child, name, cfg, sink)
+}
+
+fn start(mut child: Child, name: String, cfg: &config::RecordingConfig, sink: SourceSink) -> Result<OpenInput, String> {
+    let Some(stdout) = child.stdout.take() else {
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(format!("{PAREC} has no output"));
+    };
+    let (mut writer, reader) = sink.ring();
+    sink.start(reader, MONITOR_SAMPLE_RATE);
+
+    let stopping = Arc::new(AtomicBool::new(false));
+    let thread_stopping = Arc::clone(&stopping);
+    let errors = sink.errors.clone();
+    let generation = sink.generation;
+    let mut downmixer = Downmixer::new(cfg.channel_mode, cfg.channel_index, MONITOR_CHANNELS);
+    let thread = std::thread::spawn(move || {
+        let mut mono = Vec::with_capacity(CHUNK_FRAMES);
+        read_frames(stdout, |samples| {
+            mono.clear();
+            downmixer.process(samples, |s| s, &mut mono);
+            writer.push(&mono);
+        });
+        // Only an unexpected end means the source went away.
+        if !thread_stopping.load(Ordering::SeqCst) {
+            let _ = errors.send((generation, cpal::StreamError::DeviceNotAvailable));
+        }
+    });
+
+    Ok(OpenInput {
+        name,
+        sample_rate: MONITOR_SAMPLE_RATE,
+        _source: InputSource::Monitor {
+            _monitor: MonitorCapture {
+                child,
+                stopping,
+                thread: Some(thread),
+            },
+        },
+    })
+}
+
+/// Hands whole interleaved frames read from `stdout` to `emit` until it ends.
+fn read_frames(mut stdout: ChildStdout, mut emit: impl FnMut(&[f32])) {
+    let mut bytes = vec![0u8; CHUNK_FRAMES * FRAME_BYTES];
+    let mut samples = Vec::with_capacity(CHUNK_FRAMES * MONITOR_CHANNELS);
+    let mut filled = 0;
+    loop {
+        match stdout.read(&mut bytes[filled..]) {
+            Ok(0) => return,
+            Ok(read) => filled += read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(_) => return,
+        }
+        let whole = filled - filled % FRAME_BYTES;
+        samples.clear();
+        samples.extend(
+            bytes[..whole]
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+        );
+        emit(&samples);
+        bytes.copy_within(whole..filled, 0);
+        filled -= whole;
+    }
+}
+
+/// A running `parec`; dropping it stops the recording before returning.
+pub(super) struct MonitorCapture {
+    child: Child,
+    stopping: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for MonitorCapture {
+    fn drop(&mut self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::Capture;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn passes_the_source_on_the_command_line() {
+        let args = parec_args("alsa_output.usb-headset.analog-stereo.monitor");
+        assert_eq!(args[0], "--device=alsa_output.usb-headset.analog-stereo.monitor");
+        assert!(args.contains(&"--format=float32le".to_string()));
+        assert!(args.contains(&"--channels=2".to_string()));
+    }
+
+    #[test]
+    fn reads_stereo_frames_and_reports_an_unexpected_end() {
+        // 1,001 frames, so reads do not line up with frames.
+        let mut raw = Vec::new();
+        for _ in 0..1_001 {
+            raw.extend(0.25f32.to_le_bytes());
+            raw.extend(0.75f32.to_le_bytes());
+        }
+        let path = std::env::temp_dir().join(format!("voicedictation-monitor-test-{}.raw", std::process::id()));
+        std::fs::write(&path, &raw).unwrap();
+        let child = Command::new("cat").arg(&path).stdout(Stdio::piped()).spawn().unwrap();
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let (errors, errors_rx) = crossbeam_channel::unbounded();
+        let sink = SourceSink {
+            tx,
+            errors,
+            generation: 3,
+            ring_capacity: 4_096,
+            pause: crate::audio::ring::Pause::default(),
+        };
+        let input = start(child, "monitor".to_string(), &config::RecordingConfig::default(), sink).unwrap();
+        let Ok(Capture::Input { ring: mut reader, sample_rate: MONITOR_SAMPLE_RATE }) = rx.try_recv() else {
+            panic!("expected the monitor's ring");
+        };
+
+        let ended = errors_rx.recv_timeout(Duration::from_secs(5));
+        let _ = std::fs::remove_file(&path);
+        assert!(matches!(ended, Ok((3, cpal::StreamError::DeviceNotAvailable))));
+        let started = Instant::now();
+        let mut samples = Vec::new();
+        while samples.len() < 1_001 && started.elapsed() < Duration::from_secs(1) {
+            reader.read(&mut samples);
+        }
+        assert_eq!(samples, vec![0.5; 1_001]);
+        drop(input);
+    }
+}
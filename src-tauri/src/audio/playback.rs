@@ -0,0 +1,152 @@
+use super::resample::Resampler;
+use super::{device, RecordedAudio, OUTPUT_SAMPLE_RATE};
+use crate::config::ResampleQuality;
+use cpal::traits::{DeviceTrait, StreamTrait};
+use crossbeam_channel::Sender;
+use std::time::Duration;
+
+/// Extra time allowed past the clip's length before playback counts as stuck.
+const PLAYBACK_GRACE: Duration = Duration::from_secs(2);
+/// Time given to the device to play out what it has buffered after the last sample.
+const PLAYBACK_DRAIN: Duration = Duration::from_millis(250);
+
+/// Plays a finished recording on `output_device` (the default output for
+/// `None`) and returns the device's name once it has been heard.
+pub fn play_recording(
+    audio: &RecordedAudio,
+    output_device: Option<&str>,
+    quality: ResampleQuality,
+) -> Result<String, String> {
+    let samples = read_samples(&audio.wav_bytes()?)?;
+
+    let host = cpal::default_host();
+    let (device, name) = device::select_output_device(&host, output_device)?;
+    let default_config = device
+        .default_output_config()
+        .map_err(|e| format!("failed to get default output config: {e}"))?;
+    let sample_rate = default_config.sample_rate().0;
+    let channels = default_config.channels() as usize;
+
+    let samples = if sample_rate == OUTPUT_SAMPLE_RATE {
+        samples
+    } else {
+        let mut resampler = Resampler::new(quality, OUTPUT_SAMPLE_RATE, sample_rate)?;
+        let mut out = Vec::with_capacity(samples.len() * sample_rate as usize / OUTPUT_SAMPLE_RATE as usize);
+        resampler.process(&samples, &mut out)?;
+        resampler.finish(&mut out)?;
+        out
+    };
+    let duration = Duration::from_secs_f64(samples.len() as f64 / sample_rate as f64);
+
+    let (done_tx, done_rx) = crossbeam_channel::bounded(2);
+    let playout = Playout::new(samples, channels, done_tx.clone());
+    let stream_name = name.clone();
+    let err_fn = move |err: cpal::StreamError| {
+        let _ = done_tx.try_send(Err(format!("audio output stream error on \"{stream_name}\": {err}")));
+    };
+    let sample_format = default_config.sample_format();
+    let config: cpal::StreamConfig = default_config.into();
+    let stream = match sample_format {
+        cpal::SampleFormat::I8 => build_output::<i8>(&device, &config, playout, err_fn)?,
+        cpal::SampleFormat::I16 => build_output::<i16>(&device, &config, playout, err_fn)?,
+        cpal::SampleFormat::I32 => build_output::<i32>(&device, &config, playout, err_fn)?,
+        cpal::SampleFormat::I64 => build_output::<i64>(&device, &config, playout, err_fn)?,
+        cpal::SampleFormat::U8 => build_output::<u8>(&device, &config, playout, err_fn)?,
+        cpal::SampleFormat::U16 => build_output::<u16>(&device, &config, playout, err_fn)?,
+        cpal::SampleFormat::U32 => build_output::<u32>(&device, &config, playout, err_fn)?,
+        cpal::SampleFormat::U64 => build_output::<u64>(&device, &config, playout, err_fn)?,
+        cpal::SampleFormat::F32 => build_output::<f32>(&device, &config, playout, err_fn)?,
+        cpal::SampleFormat::F64 => build_output::<f64>(&device, &config, playout, err_fn)?,
+        other => return Err(format!("unsupported sample format: {other:?}")),
+    };
+    stream
+        .play()
+        .map_err(|e| format!("failed to start output stream: {e}"))?;
+
+    done_rx
+        .recv_timeout(duration + PLAYBACK_GRACE)
+        .map_err(|_| format!("playback on \"{name}\" did not finish"))??;
+    std::thread::sleep(PLAYBACK_DRAIN);
+    Ok(name)
+}
+
+/// Decodes one of our 16 kHz mono 16-bit recordings.
+fn read_samples(wav: &[u8]) -> Result<Vec<f32>, String> {
+    let reader =
+        hound::WavReader::new(std::io::Cursor::new(wav)).map_err(|e| format!("failed to read recording: {e}"))?;
+    reader
+        .into_samples::<i16>()
+        .map(|s| s.map(|s| s as f32 / i16::MAX as f32))
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("failed to read recording: {e}"))
+}
+
+fn build_output<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mut playout: Playout,
+    err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
+) -> Result<cpal::Stream, String>
+where
+    T: cpal::SizedSample + cpal::FromSample<f32>,
+{
+    device
+        .build_output_stream(config, move |data: &mut [T], _| playout.fill(data), err_fn, None)
+        .map_err(|e| format!("failed to build output stream: {e}"))
+}
+
+/// Feeds a mono clip to every channel of an output buffer, then silence.
+struct Playout {
+    samples: Vec<f32>,
+    position: usize,
+    channels: usize,
+    done: Option<Sender<Result<(), String>>>,
+}
+
+impl Playout {
+    fn new(samples: Vec<f32>, channels: usize, done: Sender<Result<(), String>>) -> Self {
+        Self {
+            samples,
+            position: 0,
+            channels: channels.max(1),
+            done: Some(done),
+        }
+    }
+
+    fn fill<T: cpal::Sample + cpal::FromSample<f32>>(&mut self, data: &mut [T]) {
+        for frame in data.chunks_mut(self.channels) {
+            let sample = self.samples.get(self.position).copied().unwrap_or(0.0);
+            self.position = (self.position + 1).min(self.samples.len());
+            frame.fill(T::from_sample(sample));
+        }
+        if self.position == self.samples.len() {
+            if let Some(done) = self.done.take() {
+                let _ = done.try_send(Ok(()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn playout_copies_mono_to_all_channels_then_signals_done() {
+        let (done_tx, done_rx) = crossbeam_channel::bounded(2);
+        let mut playout = Playout::new(vec![0.5, -0.5, 0.25], 2, done_tx);
+
+        let mut buffer = [0i16; 4];
+        playout.fill(&mut buffer);
+        assert_eq!(buffer, [16_384, 16_384, -16_384, -16_384]);
+        assert!(done_rx.try_recv().is_err());
+
+        let mut buffer = [1.0f32; 6];
+        playout.fill(&mut buffer);
+        assert_eq!(buffer, [0.25, 0.25, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(done_rx.try_recv(), Ok(Ok(())));
+
+        playout.fill(&mut buffer);
+        assert!(done_rx.try_recv().is_err());
+    }
+}
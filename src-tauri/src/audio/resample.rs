@@ -0,0 +1,264 @@
+use crate::config::ResampleQuality;
+use rubato::{
+    Resampler as _, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
+};
+use std::collections::VecDeque;
+
+/// Input frames handed to the sinc resampler per call.
+const SINC_CHUNK: usize = 1_024;
+
+/// Streaming mono resampler used between capture and the 16 kHz output.
+pub enum Resampler {
+    Linear(LinearResampler),
+    Sinc(SincResampler),
+}
+
+impl Resampler {
+    pub fn new(quality: ResampleQuality, in_rate: u32, out_rate: u32) -> Result<Self, String> {
+        match quality {
+            ResampleQuality::Linear => Ok(Self::Linear(LinearResampler::new(in_rate, out_rate))),
+            ResampleQuality::Fast => SincResampler::new(in_rate, out_rate, 64, SincInterpolationType::Linear).map(Self::Sinc),
+            ResampleQuality::High => SincResampler::new(in_rate, out_rate, 256, SincInterpolationType::Cubic).map(Self::Sinc),
+        }
+    }
+
+    /// Resamples `input`, appending whatever output is ready to `out`.
+    pub fn process(&mut self, input: &[f32], out: &mut Vec<f32>) -> Result<(), String> {
+        match self {
+            Self::Linear(r) => {
+                r.push(input);
+                while let Some(sample) = r.next() {
+                    out.push(sample);
+                }
+                Ok(())
+            }
+            Self::Sinc(r) => r.process(input, out),
+        }
+    }
+
+    /// Flushes samples still held back by the filter once input has ended.
+    pub fn finish(&mut self, out: &mut Vec<f32>) -> Result<(), String> {
+        match self {
+            Self::Linear(_) => Ok(()),
+            Self::Sinc(r) => r.finish(out),
+        }
+    }
+}
+
+/// Band-limited windowed-sinc resampler. The filter's cutoff sits just below the
+/// lower of the two Nyquist frequencies, so content that cannot be represented
+/// at the output rate is removed instead of aliasing into the speech band.
+pub struct SincResampler {
+    inner: SincFixedIn<f32>,
+    ratio: f64,
+    input: Vec<f32>,
+    output: Vec<Vec<f32>>,
+    frames_in: u64,
+    frames_out: u64,
+}
+
+impl SincResampler {
+    fn new(in_rate: u32, out_rate: u32, sinc_len: usize, interpolation: SincInterpolationType) -> Result<Self, String> {
+        let window = WindowFunction::BlackmanHarris2;
+        let params = SincInterpolationParameters {
+            sinc_len,
+            f_cutoff: rubato::calculate_cutoff(sinc_len, window),
+            oversampling_factor: 128,
+            interpolation,
+            window,
+        };
+        let ratio = out_rate as f64 / in_rate as f64;
+        let inner = SincFixedIn::<f32>::new(ratio, 1.0, params, SINC_CHUNK, 1)
+            .map_err(|e| format!("failed to create resampler: {e}"))?;
+        let output = inner.output_buffer_allocate(true);
+        Ok(Self {
+            inner,
+            ratio,
+            input: Vec::with_capacity(SINC_CHUNK),
+            output,
+            frames_in: 0,
+            frames_out: 0,
+        })
+    }
+
+    fn process(&mut self, input: &[f32], out: &mut Vec<f32>) -> Result<(), String> {
+        self.frames_in += input.len() as u64;
+        let mut rest = input;
+        while !rest.is_empty() {
+            let take = (SINC_CHUNK - self.input.len()).min(rest.len());
+            self.input.extend_from_slice(&rest[..take]);
+            rest = &rest[take..];
+            if self.input.len() == SINC_CHUNK {
+                let (_, n) = self
+                    .inner
+                    .process_into_buffer(&[&self.input], &mut self.output, None)
+                    .map_err(|e| format!("resampling failed: {e}"))?;
+                self.input.clear();
+                self.emit(n, out);
+            }
+        }
+        Ok(())
+    }
+
+    /// `SincFixedIn` starts its filter centred on the first input sample, so the
+    /// output is already aligned with the input; the last half filter length
+    /// only comes out once zeros are pushed after the end of the stream.
+    fn finish(&mut self, out: &mut Vec<f32>) -> Result<(), String> {
+        let expected = (self.frames_in as f64 * self.ratio).round() as u64;
+        let mut pending = Some(std::mem::take(&mut self.input));
+        while self.frames_out < expected {
+            let input = pending.take().filter(|v| !v.is_empty());
+            let (_, n) = self
+                .inner
+                .process_partial_into_buffer(input.as_ref().map(std::slice::from_ref), &mut self.output, None)
+                .map_err(|e| format!("resampling failed: {e}"))?;
+            let n = n.min((expected - self.frames_out) as usize);
+            self.emit(n, out);
+        }
+        Ok(())
+    }
+
+    fn emit(&mut self, n: usize, out: &mut Vec<f32>) {
+        out.extend_from_slice(&self.output[0][..n]);
+        self.frames_out += n as u64;
+    }
+}
+
+pub struct LinearResampler {
+    ratio: f64,
+    pos: f64,
+    buf: VecDeque<f32>,
+}
+
+impl LinearResampler {
+    pub fn new(in_rate: u32, out_rate: u32) -> Self {
+        Self {
+            ratio: in_rate as f64 / out_rate as f64,
+            pos: 0.0,
+            buf: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, samples: &[f32]) {
+        self.buf.extend(samples.iter().copied());
+    }
+
+    pub fn next(&mut self) -> Option<f32> {
+        let i0 = self.pos.floor() as usize;
+        let i1 = i0 + 1;
+        if i1 >= self.buf.len() {
+            return None;
+        }
+
+        let s0 = self.buf[i0];
+        let s1 = self.buf[i1];
+        let frac = (self.pos - i0 as f64) as f32;
+        let out = s0 + (s1 - s0) * frac;
+
+        self.pos += self.ratio;
+
+        let drop_count = self.pos.floor() as usize;
+        if drop_count > 0 {
+            for _ in 0..drop_count {
+                let _ = self.buf.pop_front();
+            }
+            self.pos -= drop_count as f64;
+        }
+
+        Some(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| 0.5 * (2.0 * std::f32::consts::PI * freq * i as f32 / rate as f32).sin())
+            .collect()
+    }
+
+    fn resample_all(quality: ResampleQuality, in_rate: u32, input: &[f32], chunk: usize) -> Vec<f32> {
+        let mut r = Resampler::new(quality, in_rate, 16_000).expect("resampler");
+        let mut out = Vec::new();
+        for c in input.chunks(chunk) {
+            r.process(c, &mut out).expect("process");
+        }
+        r.finish(&mut out).expect("finish");
+        out
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn resamples_by_integer_ratio_without_interpolation_error() {
+        // 48k -> 16k is an exact 3:1 ratio.
+        let mut r = LinearResampler::new(48_000, 16_000);
+        let input: Vec<f32> = (0..480).map(|i| i as f32).collect();
+        r.push(&input);
+
+        let mut out = Vec::new();
+        while let Some(v) = r.next() {
+            out.push(v);
+            if out.len() > 1_000 {
+                panic!("unexpectedly large output");
+            }
+        }
+
+        assert!(out.len() >= 100);
+        for (i, v) in out.iter().take(100).enumerate() {
+            assert_eq!(*v, (i * 3) as f32);
+        }
+    }
+
+    #[test]
+    fn sinc_output_length_matches_rate_ratio() {
+        let input: Vec<f32> = (0..480).map(|i| i as f32).collect();
+        // Same input as the linear test above, which yields at least 100 samples.
+        assert_eq!(resample_all(ResampleQuality::High, 48_000, &input, 480).len(), 160);
+        assert_eq!(resample_all(ResampleQuality::Fast, 48_000, &input, 7).len(), 160);
+
+        let input = vec![0.0; 44_100];
+        assert_eq!(resample_all(ResampleQuality::High, 44_100, &input, 441).len(), 16_000);
+    }
+
+    #[test]
+    fn sinc_keeps_passband_level_and_alignment() {
+        let input = sine(1_000.0, 48_000, 48_000);
+        let out = resample_all(ResampleQuality::High, 48_000, &input, 512);
+        let expected = sine(1_000.0, 16_000, 16_000);
+
+        // Ignore the edges where the filter sees the implicit zeros.
+        let level_db = 20.0 * (rms(&out[200..15_800]) / rms(&expected[200..15_800])).log10();
+        assert!(level_db.abs() < 0.1, "passband level {level_db} dB");
+
+        // The output must line up with the input to within one output sample.
+        let correlation = |lag: i32| -> f32 {
+            (200..15_800)
+                .map(|i| out[i] * expected[(i as i32 + lag) as usize])
+                .sum()
+        };
+        let best = (-4..=4).max_by(|a, b| correlation(*a).total_cmp(&correlation(*b))).unwrap();
+        assert!(best.abs() <= 1, "output is offset by {best} samples");
+    }
+
+    #[test]
+    fn sinc_rejects_content_above_output_nyquist() {
+        // 12 kHz is above the 8 kHz output Nyquist; linear decimation folds it
+        // back to 4 kHz almost unattenuated.
+        let input = sine(12_000.0, 48_000, 48_000);
+
+        let linear = resample_all(ResampleQuality::Linear, 48_000, &input, 512);
+        let linear_db = 20.0 * (rms(&linear[200..15_800]) / (0.5 / 2f32.sqrt())).log10();
+        assert!(linear_db > -3.0, "linear alias level {linear_db} dB");
+
+        for quality in [ResampleQuality::Fast, ResampleQuality::High] {
+            let sinc = resample_all(quality, 48_000, &input, 512);
+            let sinc_db = 20.0 * (rms(&sinc[200..15_800]) / (0.5 / 2f32.sqrt())).log10();
+            assert!(sinc_db < -60.0, "{quality:?} alias level {sinc_db} dB");
+        }
+    }
+}
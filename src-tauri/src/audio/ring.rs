@@ -0,0 +1,139 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Creates a preallocated single-producer ring holding up to `capacity` samples.
+#[cfg(test)]
+pub fn ring(capacity: usize) -> (RingWriter, RingReader) {
+    pausable_ring(capacity, &Pause::default())
+}
+
+/// Creates a preallocated single-producer ring holding up to `capacity`
+/// samples; samples pushed while `pause` is set are discarded.
+pub fn pausable_ring(capacity: usize, pause: &Pause) -> (RingWriter, RingReader) {
+    let (producer, consumer) = rtrb::RingBuffer::new(capacity.max(1));
+    let dropped = Arc::new(AtomicU64::new(0));
+    (
+        RingWriter {
+            producer,
+            dropped: Arc::clone(&dropped),
+            pause: pause.clone(),
+        },
+        RingReader { consumer, dropped },
+    )
+}
+
+/// Pauses the rings it was given to. It acts where samples are pushed, so
+/// audio is kept or discarded by when it was captured, however far behind
+/// the reader is.
+#[derive(Clone, Default)]
+pub struct Pause(Arc<AtomicBool>);
+
+impl Pause {
+    pub fn set(&self, paused: bool) {
+        self.0.store(paused, Ordering::Relaxed);
+    }
+
+    fn is_set(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// The audio thread's end of a ring. Writing never blocks or allocates;
+/// samples that do not fit are dropped and counted.
+pub struct RingWriter {
+    producer: rtrb::Producer<f32>,
+    dropped: Arc<AtomicU64>,
+    pause: Pause,
+}
+
+impl RingWriter {
+    pub fn push(&mut self, samples: &[f32]) {
+        if self.pause.is_set() {
+            return;
+        }
+        let fits = samples.len().min(self.producer.slots());
+        if let Ok(chunk) = self.producer.write_chunk_uninit(fits) {
+            chunk.fill_from_iter(samples[..fits].iter().copied());
+        }
+        let dropped = samples.len() - fits;
+        if dropped > 0 {
+            self.dropped.fetch_add(dropped as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Samples that fit before the ring is full.
+    pub fn space(&self) -> usize {
+        self.producer.slots()
+    }
+
+    /// Counts `samples` lost before they reached this ring, e.g. by an input
+    /// feeding the writer.
+    pub fn add_dropped(&self, samples: u64) {
+        if samples > 0 {
+            self.dropped.fetch_add(samples, Ordering::Relaxed);
+        }
+    }
+}
+
+/// The processing thread's end of a ring.
+pub struct RingReader {
+    consumer: rtrb::Consumer<f32>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl RingReader {
+    /// Moves everything currently buffered to `out`.
+    pub fn read(&mut self, out: &mut Vec<f32>) {
+        if let Ok(chunk) = self.consumer.read_chunk(self.consumer.slots()) {
+            let (first, second) = chunk.as_slices();
+            out.extend_from_slice(first);
+            out.extend_from_slice(second);
+            chunk.commit_all();
+        }
+    }
+
+    /// Samples the writer had to drop because the ring was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_and_counts_what_does_not_fit() {
+        let (mut writer, mut reader) = ring(4);
+        writer.push(&[1.0, 2.0, 3.0]);
+        writer.push(&[4.0, 5.0, 6.0]);
+        assert_eq!(reader.dropped(), 2);
+
+        let mut out = Vec::new();
+        reader.read(&mut out);
+        assert_eq!(out, vec![1.0, 2.0, 3.0, 4.0]);
+
+        // Space freed by reading is reused, across the wrap-around.
+        writer.push(&[7.0, 8.0, 9.0]);
+        out.clear();
+        reader.read(&mut out);
+        assert_eq!(out, vec![7.0, 8.0, 9.0]);
+        assert_eq!(reader.dropped(), 2);
+    }
+
+    #[test]
+    fn discards_what_is_pushed_while_paused() {
+        let pause = Pause::default();
+        let (mut writer, mut reader) = pausable_ring(8, &pause);
+        writer.push(&[1.0]);
+        pause.set(true);
+        writer.push(&[2.0]);
+        pause.set(false);
+        writer.push(&[3.0]);
+
+        let mut out = Vec::new();
+        reader.read(&mut out);
+        assert_eq!(out, vec![1.0, 3.0]);
+        assert_eq!(reader.dropped(), 0);
+    }
+}
@@ -0,0 +1,136 @@
+use super::vad::{EndpointDetector, Vad};
+use crate::config;
+
+/// Decides where a segment of a recording ends: at the first pause in speech
+/// once it is `pause_from` samples long, or at `max_samples` if the speaker
+/// never pauses.
+pub struct Segmenter {
+    written: u64,
+    pause_from: u64,
+    max_samples: u64,
+    vad: Vad,
+    pause: EndpointDetector,
+    due: bool,
+}
+
+impl Segmenter {
+    pub fn new(sample_rate: u32, threshold_db: f32, pause: EndpointDetector, pause_from: u64, max_samples: u64) -> Self {
+        Self {
+            written: 0,
+            pause_from,
+            max_samples: max_samples.max(1),
+            vad: Vad::new(sample_rate, threshold_db),
+            pause,
+            due: false,
+        }
+    }
+
+    /// With `recording.incremental`, ends at every pause after speech (and at
+    /// `maxSeconds` with rollover too). With `recording.rollover` alone, ends
+    /// at `maxSeconds` or at a pause in its last `searchSeconds`. `None` when
+    /// neither is enabled.
+    pub fn for_config(cfg: &config::RecordingConfig, sample_rate: u32) -> Option<Self> {
+        let max_samples = cfg.max_seconds.max(1) * sample_rate as u64;
+        if cfg.incremental.enabled {
+            return Some(Self::new(
+                sample_rate,
+                cfg.vad.threshold_db,
+                EndpointDetector::new(cfg.incremental.pause_ms),
+                0,
+                if cfg.rollover.enabled { max_samples } else { u64::MAX },
+            ));
+        }
+        if !cfg.rollover.enabled {
+            return None;
+        }
+        let search_samples = cfg.rollover.search_seconds * sample_rate as u64;
+        Some(Self::new(
+            sample_rate,
+            cfg.vad.threshold_db,
+            EndpointDetector::armed(cfg.rollover.pause_ms),
+            max_samples.saturating_sub(search_samples),
+            max_samples,
+        ))
+    }
+
+    pub fn push(&mut self, samples: &[f32]) {
+        let start = self.written;
+        self.written += samples.len() as u64;
+        if self.written > self.pause_from {
+            let skip = self.pause_from.saturating_sub(start) as usize;
+            if self.vad.push(&samples[skip..]).into_iter().any(|speech| self.pause.update(speech)) {
+                self.due = true;
+            }
+        }
+        if self.written >= self.max_samples {
+            self.due = true;
+        }
+    }
+
+    /// Samples the segment takes before it reaches `max_samples`.
+    pub fn room(&self) -> u64 {
+        self.max_samples.saturating_sub(self.written)
+    }
+
+    /// The segment should end after what has been pushed so far.
+    pub fn is_due(&self) -> bool {
+        self.due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16_000;
+
+    fn speech(len: usize) -> Vec<f32> {
+        (0..len).map(|i| 0.5 * (i as f32 * 0.2).sin()).collect()
+    }
+
+    #[test]
+    fn ends_at_a_pause_only_inside_the_search_window() {
+        let mut segmenter = Segmenter::new(RATE, -40.0, EndpointDetector::armed(300), RATE as u64, 10 * RATE as u64);
+        segmenter.push(&speech(8_000));
+        segmenter.push(&[0.0; 8_000]);
+        assert!(!segmenter.is_due(), "a pause before the window is ignored");
+
+        segmenter.push(&speech(8_000));
+        segmenter.push(&[0.0; 3_200]);
+        assert!(!segmenter.is_due());
+        segmenter.push(&[0.0; 1_600]);
+        assert!(segmenter.is_due());
+    }
+
+    #[test]
+    fn incremental_segments_end_at_each_pause_after_speech() {
+        let cfg = config::RecordingConfig {
+            incremental: config::IncrementalConfig {
+                enabled: true,
+                pause_ms: 300,
+            },
+            ..Default::default()
+        };
+        let mut segmenter = Segmenter::for_config(&cfg, RATE).unwrap();
+        segmenter.push(&[0.0; 3 * RATE as usize]);
+        assert!(!segmenter.is_due(), "silence alone is not a stretch of speech");
+        assert_eq!(segmenter.room(), u64::MAX - 3 * RATE as u64);
+
+        segmenter.push(&speech(8_000));
+        segmenter.push(&[0.0; 3_200]);
+        assert!(!segmenter.is_due());
+        segmenter.push(&[0.0; 1_600]);
+        assert!(segmenter.is_due());
+    }
+
+    #[test]
+    fn ends_at_the_limit_without_a_pause() {
+        let mut segmenter = Segmenter::new(RATE, -40.0, EndpointDetector::armed(300), RATE as u64, 2 * RATE as u64);
+        segmenter.push(&speech(RATE as usize));
+        assert!(!segmenter.is_due());
+        assert_eq!(segmenter.room(), RATE as u64);
+        segmenter.push(&speech(RATE as usize));
+        assert!(segmenter.is_due());
+        assert_eq!(segmenter.room(), 0);
+    }
+}
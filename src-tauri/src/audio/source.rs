@@ -0,0 +1,581 @@
+use super::mix::{Mixer, MIX_SAMPLE_RATE};
+use super::ring::{self, RingReader, RingWriter};
+#[cfg(target_os = "linux")]
+use super::monitor;
+use super::{device, file, loopback, open_input, open_stream, Capture, InputSource, OpenInput, RecordingEvent, WarmInput};
+use crate::config::{self, CaptureMode, SourceConfig, SourceKind};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Overrides `recording.source`, e.g. on CI machines without a sound card.
+const SOURCE_ENV: &str = "VOICEDICTATION_AUDIO_SOURCE";
+
+/// Buffer length synthetic sources deliver, like a device callback would.
+const PLAYBACK_CHUNK_MS: u64 = 20;
+/// How often a fast clip checks for room in a full ring.
+const FAST_POLL: Duration = Duration::from_millis(1);
+/// How often the meeting mixer collects both inputs.
+const MIX_INTERVAL: Duration = Duration::from_millis(10);
+/// Level of the tone source; well above the VAD threshold.
+const TONE_AMPLITUDE: f32 = 0.25;
+/// Rate the tone source runs at.
+const TONE_SAMPLE_RATE: u32 = 48_000;
+
+/// Where a recording's audio comes from.
+pub(super) trait AudioSource {
+    /// Starts delivering mono buffers to `sink` until the returned input is dropped.
+    fn open(&mut self, sink: SourceSink) -> Result<OpenInput, String>;
+
+    /// Opens a replacement after the input named `lost` went away, if this
+    /// source has one.
+    fn replace(&mut self, _lost: &str, _sink: SourceSink) -> Option<OpenInput> {
+        None
+    }
+}
+
+/// The recording's end of a source. Each input gets a ring of
+/// `ring_capacity` samples; stream errors go to `errors`, tagged with
+/// `generation` so ones from a replaced input are ignored.
+pub(super) struct SourceSink {
+    pub tx: Sender<Capture>,
+    pub errors: Sender<(u32, cpal::StreamError)>,
+    pub generation: u32,
+    pub ring_capacity: usize,
+    pub pause: ring::Pause,
+}
+
+impl SourceSink {
+    /// A preallocated ring for one input; the writer goes to the audio thread.
+    pub fn ring(&self) -> (RingWriter, RingReader) {
+        ring::pausable_ring(self.ring_capacity, &self.pause)
+    }
+
+    /// Hands `reader` to the recording; it is read once everything sent
+    /// before it has been processed.
+    pub fn start(&self, reader: RingReader, sample_rate: u32) {
+        let _ = self.tx.send(Capture::Input { ring: reader, sample_rate });
+    }
+
+    /// The same sink delivering to `tx`, for a source feeding another one.
+    /// Only the outer source is paused, so the inner ones keep running.
+    fn redirect(&self, tx: Sender<Capture>) -> SourceSink {
+        SourceSink {
+            tx,
+            errors: self.errors.clone(),
+            generation: self.generation,
+            ring_capacity: self.ring_capacity,
+            pause: ring::Pause::default(),
+        }
+    }
+}
+
+/// `recording.source` with the environment override applied.
+pub fn effective_source(cfg: &SourceConfig) -> Result<SourceConfig, String> {
+    match std::env::var(SOURCE_ENV) {
+        Ok(spec) if !spec.trim().is_empty() => parse_source(spec.trim(), cfg),
+        _ => Ok(cfg.clone()),
+    }
+}
+
+/// Parses `device`, `tone`, `tone:<hz>`, `file:<path>` or `fast-file:<path>`.
+fn parse_source(spec: &str, base: &SourceConfig) -> Result<SourceConfig, String> {
+    let (kind, arg) = match spec.split_once(':') {
+        Some((kind, arg)) => (kind, Some(arg)),
+        None => (spec, None),
+    };
+    let mut source = base.clone();
+    match (kind, arg) {
+        ("device", None) => source.kind = SourceKind::Device,
+        ("tone", None) => source.kind = SourceKind::Tone,
+        ("tone", Some(hz)) => {
+            source.kind = SourceKind::Tone;
+            source.tone_hz = hz
+                .parse()
+                .map_err(|_| format!("{SOURCE_ENV}: invalid tone frequency \"{hz}\""))?;
+        }
+        ("file" | "fast-file", Some(path)) if !path.is_empty() => {
+            source.kind = SourceKind::File;
+            source.file_path = Some(path.to_string());
+            source.fast_playback = kind == "fast-file";
+        }
+        _ => {
+            return Err(format!(
+                "{SOURCE_ENV}: expected \"device\", \"tone[:<hz>]\", \"file:<path>\" or \"fast-file:<path>\", got \"{spec}\""
+            ))
+        }
+    }
+    Ok(source)
+}
+
+/// Builds the source `cfg` asks for. `warm_input` is only used for microphone
+/// capture and `channels_copy` only for meetings.
+pub(super) fn open_source(
+    cfg: &config::RecordingConfig,
+    warm_input: Option<WarmInput>,
+    channels_copy: Option<PathBuf>,
+    events: Sender<RecordingEvent>,
+) -> Result<Box<dyn AudioSource>, String> {
+    let source = effective_source(&cfg.source)?;
+    Ok(match source.kind {
+        SourceKind::Device if cfg.capture == CaptureMode::Meeting => Box::new(MeetingSource {
+            microphone: Box::new(DeviceSource {
+                host: cpal::default_host(),
+                cfg: cfg.clone(),
+                warm_input: None,
+                events: events.clone(),
+            }),
+            system: Box::new(LoopbackSource {
+                host: cpal::default_host(),
+                cfg: cfg.clone(),
+            }),
+            cfg: cfg.clone(),
+            channels_copy,
+            events,
+        }),
+        SourceKind::Device if cfg.capture == CaptureMode::SystemAudio => Box::new(LoopbackSource {
+            host: cpal::default_host(),
+            cfg: cfg.clone(),
+        }),
+        SourceKind::Device => Box::new(DeviceSource {
+            host: cpal::default_host(),
+            cfg: cfg.clone(),
+            warm_input,
+            events,
+        }),
+        SourceKind::File => {
+            let path = source
+                .file_path
+                .ok_or_else(|| "recording.source.filePath is required for the file source".to_string())?;
+            let (samples, sample_rate) = file::read_mono(Path::new(&path), cfg)?;
+            Box::new(PlaybackSource {
+                name: format!("file \"{path}\""),
+                sample_rate,
+                signal: Signal::Clip(samples.into()),
+                fast: source.fast_playback,
+            })
+        }
+        SourceKind::Tone => Box::new(PlaybackSource {
+            name: format!("{} Hz tone", source.tone_hz),
+            sample_rate: TONE_SAMPLE_RATE,
+            signal: Signal::Tone(source.tone_hz),
+            fast: false,
+        }),
+    })
+}
+
+/// An input device through cpal, taken from the warm input when one is open.
+struct DeviceSource {
+    host: cpal::Host,
+    cfg: config::RecordingConfig,
+    warm_input: Option<WarmInput>,
+    events: Sender<RecordingEvent>,
+}
+
+impl AudioSource for DeviceSource {
+    fn open(&mut self, sink: SourceSink) -> Result<OpenInput, String> {
+        let attached = self
+            .warm_input
+            .take()
+            .and_then(|warm| warm.attach(&sink));
+        if let Some(attachment) = attached {
+            return Ok(OpenInput {
+                name: attachment.device_name().to_string(),
+                sample_rate: attachment.sample_rate(),
+                _source: InputSource::Warm { _attachment: attachment },
+            });
+        }
+
+        let selected = device::select_input_device(&self.host, self.cfg.input_device.as_deref())?;
+        if let Some(requested) = selected.fell_back_from {
+            let _ = self.events.send(RecordingEvent::InputDeviceFallback {
+                requested,
+                using: selected.name.clone(),
+            });
+        }
+        open_input(&selected.device, selected.name, &self.cfg, sink)
+    }
+
+    fn replace(&mut self, lost: &str, sink: SourceSink) -> Option<OpenInput> {
+        if !self.cfg.auto_switch_device {
+            return None;
+        }
+        let next = device::next_input_device(&self.host, lost)?;
+        open_input(&next.device, next.name, &self.cfg, sink).ok()
+    }
+}
+
+/// What is playing on an output device, through loopback or a monitor source.
+struct LoopbackSource {
+    host: cpal::Host,
+    cfg: config::RecordingConfig,
+}
+
+impl AudioSource for LoopbackSource {
+    fn open(&mut self, sink: SourceSink) -> Result<OpenInput, String> {
+        match loopback::select_loopback_device(&self.host, self.cfg.loopback_device.as_deref())? {
+            loopback::LoopbackDevice::Device { device, name } => {
+                let stream_config = loopback::stream_config(&device, &name)?;
+                open_stream(&device, stream_config, name, &self.cfg, sink)
+            }
+            #[cfg(target_os = "linux")]
+            loopback::LoopbackDevice::Monitor { source, name } => monitor::open_monitor(&source, name, &self.cfg, sink),
+        }
+    }
+}
+
+/// The input device and system audio mixed into one input.
+struct MeetingSource {
+    microphone: Box<dyn AudioSource>,
+    system: Box<dyn AudioSource>,
+    cfg: config::RecordingConfig,
+    channels_copy: Option<PathBuf>,
+    events: Sender<RecordingEvent>,
+}
+
+impl AudioSource for MeetingSource {
+    fn open(&mut self, sink: SourceSink) -> Result<OpenInput, String> {
+        let (microphone_tx, microphone_rx) = crossbeam_channel::unbounded();
+        let microphone = self.microphone.open(sink.redirect(microphone_tx))?;
+        let (system_tx, system_rx) = crossbeam_channel::unbounded();
+        let system = self.system.open(sink.redirect(system_tx))?;
+        let mut mixer = Mixer::new(
+            &self.cfg,
+            started_input(&microphone_rx, &microphone.name)?,
+            started_input(&system_rx, &system.name)?,
+            self.channels_copy.as_deref(),
+        )?;
+
+        let (stop_tx, stop_rx) = crossbeam_channel::bounded::<()>(0);
+        let (mut writer, reader) = sink.ring();
+        sink.start(reader, MIX_SAMPLE_RATE);
+        let events = self.events.clone();
+        let thread = std::thread::spawn(move || {
+            mixer.align();
+            let mut mixed = Vec::new();
+            loop {
+                let stopping = !matches!(stop_rx.recv_timeout(MIX_INTERVAL), Err(RecvTimeoutError::Timeout));
+                mixed.clear();
+                let result = if stopping {
+                    mixer.finish(&mut mixed)
+                } else {
+                    mixer.mix(&mut mixed)
+                };
+                writer.push(&mixed);
+                // The inputs' losses are the mix's, so the recording's stats count them.
+                writer.add_dropped(mixer.take_dropped());
+                if let Err(message) = result {
+                    let _ = events.send(RecordingEvent::StreamError { message });
+                }
+                if stopping {
+                    break;
+                }
+            }
+        });
+
+        Ok(OpenInput {
+            name: format!("{} + {}", microphone.name, system.name),
+            sample_rate: MIX_SAMPLE_RATE,
+            _source: InputSource::Mixed {
+                _inputs: vec![microphone, system],
+                _mixer: Playback {
+                    stop: Some(stop_tx),
+                    thread: Some(thread),
+                },
+            },
+        })
+    }
+}
+
+/// The ring an input opened for the mixer announced.
+fn started_input(rx: &Receiver<Capture>, name: &str) -> Result<(RingReader, u32), String> {
+    rx.try_iter()
+        .find_map(|capture| match capture {
+            Capture::Input { ring, sample_rate } => Some((ring, sample_rate)),
+            _ => None,
+        })
+        .ok_or_else(|| format!("\"{name}\" did not start"))
+}
+
+/// Synthetic audio delivered in buffers the size a device callback would
+/// use, in real time unless `fast`.
+struct PlaybackSource {
+    name: String,
+    sample_rate: u32,
+    signal: Signal,
+    /// Deliver a clip as fast as the ring empties, then silence in real time.
+    fast: bool,
+}
+
+#[derive(Clone)]
+enum Signal {
+    /// Decoded file, followed by silence once it has played.
+    Clip(Arc<[f32]>),
+    Tone(f32),
+}
+
+impl Signal {
+    fn fill(&self, position: usize, sample_rate: u32, out: &mut [f32]) {
+        match self {
+            Self::Clip(samples) => {
+                for (i, s) in out.iter_mut().enumerate() {
+                    *s = samples.get(position + i).copied().unwrap_or(0.0);
+                }
+            }
+            Self::Tone(hz) => {
+                let step = 2.0 * std::f64::consts::PI * *hz as f64 / sample_rate as f64;
+                for (i, s) in out.iter_mut().enumerate() {
+                    *s = TONE_AMPLITUDE * ((position + i) as f64 * step).sin() as f32;
+                }
+            }
+        }
+    }
+}
+
+impl AudioSource for PlaybackSource {
+    fn open(&mut self, sink: SourceSink) -> Result<OpenInput, String> {
+        let chunk_len = (self.sample_rate as u64 * PLAYBACK_CHUNK_MS / 1_000).max(1) as usize;
+        let chunk_duration = Duration::from_millis(PLAYBACK_CHUNK_MS);
+        let sample_rate = self.sample_rate;
+        let signal = self.signal.clone();
+        let fast_len = match &self.signal {
+            Signal::Clip(samples) if self.fast => samples.len(),
+            _ => 0,
+        };
+        let (stop_tx, stop_rx) = crossbeam_channel::bounded::<()>(0);
+        let (mut writer, reader) = sink.ring();
+        sink.start(reader, sample_rate);
+
+        let thread = std::thread::spawn(move || {
+            let mut chunk = vec![0.0; chunk_len];
+            let mut position = 0;
+            while position < fast_len {
+                // Never overfill the ring, which would drop samples.
+                let wait = if writer.space() >= chunk_len { Duration::ZERO } else { FAST_POLL };
+                if !matches!(stop_rx.recv_timeout(wait), Err(RecvTimeoutError::Timeout)) {
+                    return;
+                }
+                if writer.space() >= chunk_len {
+                    signal.fill(position, sample_rate, &mut chunk);
+                    writer.push(&chunk);
+                    position += chunk_len;
+                }
+            }
+            let started = Instant::now();
+            let mut due = chunk_duration;
+            while let Err(RecvTimeoutError::Timeout) = stop_rx.recv_timeout(due.saturating_sub(started.elapsed())) {
+                signal.fill(position, sample_rate, &mut chunk);
+                writer.push(&chunk);
+                position += chunk_len;
+                due += chunk_duration;
+            }
+        });
+
+        Ok(OpenInput {
+            name: self.name.clone(),
+            sample_rate,
+            _source: InputSource::Playback {
+                _playback: Playback {
+                    stop: Some(stop_tx),
+                    thread: Some(thread),
+                },
+            },
+        })
+    }
+}
+
+/// A running playback or mixer thread; dropping it stops delivery before returning.
+pub(super) struct Playback {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for Playback {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_environment_source_specs() {
+        let base = SourceConfig::default();
+        assert_eq!(parse_source("device", &base).unwrap(), base);
+
+        let tone = parse_source("tone:1000", &base).unwrap();
+        assert_eq!((tone.kind, tone.tone_hz), (SourceKind::Tone, 1000.0));
+        assert_eq!(parse_source("tone", &base).unwrap().tone_hz, base.tone_hz);
+
+        let file = parse_source("file:/tmp/a:b.wav", &base).unwrap();
+        assert_eq!(file.kind, SourceKind::File);
+        assert_eq!(file.file_path.as_deref(), Some("/tmp/a:b.wav"));
+        assert!(!file.fast_playback);
+        let fast = parse_source("fast-file:/tmp/a.wav", &base).unwrap();
+        assert_eq!((fast.kind, fast.fast_playback), (SourceKind::File, true));
+
+        for bad in ["file", "file:", "fast-file:", "tone:loud", "speaker"] {
+            assert!(parse_source(bad, &base).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn clip_plays_in_real_time_then_continues_with_silence() {
+        let mut source = PlaybackSource {
+            name: "clip".to_string(),
+            sample_rate: 1_000,
+            signal: Signal::Clip(vec![0.5; 30].into()),
+            fast: false,
+        };
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let (errors, _) = crossbeam_channel::unbounded();
+        let input = source
+            .open(SourceSink {
+                tx,
+                errors,
+                generation: 0,
+                ring_capacity: 1_000,
+                pause: ring::Pause::default(),
+            })
+            .unwrap();
+        assert_eq!(input.sample_rate, 1_000);
+        let Ok(Capture::Input { ring: mut reader, sample_rate: 1_000 }) = rx.try_recv() else {
+            panic!("expected the input's ring");
+        };
+
+        let started = Instant::now();
+        let mut samples = Vec::new();
+        while samples.len() < 60 {
+            assert!(started.elapsed() < Duration::from_secs(1));
+            reader.read(&mut samples);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert_eq!(&samples[..30], &[0.5; 30]);
+        assert_eq!(&samples[30..60], &[0.0; 30]);
+
+        // Dropping the input stops delivery before it returns.
+        drop(input);
+        samples.clear();
+        reader.read(&mut samples);
+        std::thread::sleep(Duration::from_millis(60));
+        samples.clear();
+        reader.read(&mut samples);
+        assert!(samples.is_empty());
+        assert_eq!(reader.dropped(), 0);
+    }
+
+    #[test]
+    fn fast_clip_fills_the_ring_without_dropping() {
+        let mut source = PlaybackSource {
+            name: "clip".to_string(),
+            sample_rate: 1_000,
+            signal: Signal::Clip(vec![0.5; 5_000].into()),
+            fast: true,
+        };
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let (errors, _) = crossbeam_channel::unbounded();
+        let input = source
+            .open(SourceSink {
+                tx,
+                errors,
+                generation: 0,
+                ring_capacity: 1_000,
+                pause: ring::Pause::default(),
+            })
+            .unwrap();
+        let Ok(Capture::Input { ring: mut reader, .. }) = rx.try_recv() else {
+            panic!("expected the input's ring");
+        };
+
+        // Five seconds of audio through a one-second ring, well within a second.
+        let started = Instant::now();
+        let mut samples = Vec::new();
+        while samples.len() < 5_000 {
+            assert!(started.elapsed() < Duration::from_secs(1), "{} samples", samples.len());
+            reader.read(&mut samples);
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        assert_eq!(&samples[..5_000], &[0.5; 5_000]);
+        drop(input);
+        assert_eq!(reader.dropped(), 0);
+    }
+
+    #[test]
+    fn meeting_mixes_both_inputs_into_one() {
+        let clip = |sample_rate: u32, level: f32| PlaybackSource {
+            name: format!("{level}"),
+            sample_rate,
+            signal: Signal::Clip(vec![level; sample_rate as usize].into()),
+            fast: false,
+        };
+        let (events, _) = crossbeam_channel::unbounded();
+        let mut source = MeetingSource {
+            microphone: Box::new(clip(MIX_SAMPLE_RATE, 0.25)),
+            system: Box::new(clip(16_000, 0.5)),
+            cfg: config::RecordingConfig {
+                resample_quality: config::ResampleQuality::Linear,
+                ..Default::default()
+            },
+            channels_copy: None,
+            events,
+        };
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let (errors, _) = crossbeam_channel::unbounded();
+        let input = source
+            .open(SourceSink {
+                tx,
+                errors,
+                generation: 0,
+                ring_capacity: MIX_SAMPLE_RATE as usize,
+                pause: ring::Pause::default(),
+            })
+            .unwrap();
+        assert_eq!(input.name, "0.25 + 0.5");
+        let Ok(Capture::Input { ring: mut reader, sample_rate: MIX_SAMPLE_RATE }) = rx.try_recv() else {
+            panic!("expected the mixed input's ring");
+        };
+
+        std::thread::sleep(Duration::from_millis(200));
+        drop(input);
+        let mut samples = Vec::new();
+        reader.read(&mut samples);
+        assert!(samples.len() > MIX_SAMPLE_RATE as usize / 10, "{}", samples.len());
+        let mixed = samples[100..samples.len() - 100].iter().filter(|s| (**s - 0.75).abs() < 1e-3).count();
+        assert!(mixed > samples.len() * 9 / 10, "{mixed} of {}", samples.len());
+    }
+
+    #[test]
+    fn records_from_the_tone_source_without_audio_hardware() {
+        let cfg = config::RecordingConfig {
+            source: SourceConfig {
+                kind: SourceKind::Tone,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (events, _) = crossbeam_channel::unbounded();
+        let options = crate::audio::RecordingOptions {
+            config: cfg,
+            events,
+            raw_copy_path: None,
+            channels_copy_path: None,
+            warm_input: None,
+            segments: None,
+        };
+        let handle = crate::audio::start_recording(crate::audio::RecordingTarget::Memory, options).unwrap();
+        std::thread::sleep(Duration::from_millis(300));
+        let finished = handle.stop(Duration::ZERO).unwrap();
+
+        assert!(finished.stats.seconds >= 0.2, "{}", finished.stats);
+        assert!((finished.stats.peak_db - 20.0 * TONE_AMPLITUDE.log10()).abs() < 0.1);
+        assert!(finished.audio.wav_bytes().unwrap().len() > 44);
+    }
+}
@@ -0,0 +1,339 @@
+use super::vad::{rms, FRAME_MS};
+use serde::Serialize;
+
+/// Samples at or above this magnitude count as clipped.
+const CLIP_LEVEL: f32 = 0.999;
+/// Below this peak level the input is treated as muted.
+const SILENT_PEAK_DB: f32 = -50.0;
+/// Loud frames (90th percentile) below this level suggest the gain is far too low.
+const LOW_LEVEL_DB: f32 = -40.0;
+/// Share of clipped samples above which the recording is reported as clipping.
+const CLIPPED_RATIO_WARNING: f32 = 0.001;
+/// Window the live level meter reports over (20 updates per second).
+const LEVEL_WINDOW_MS: u64 = 50;
+/// Floor for level computations, so digital silence maps to -120 dB.
+const LEVEL_FLOOR: f32 = 1e-6;
+
+/// Level statistics of one recording's raw input, before any processing.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputStats {
+    pub seconds: f32,
+    pub peak_db: f32,
+    pub rms_db: f32,
+    pub clipped_ratio: f32,
+    pub dc_offset: f32,
+    /// Loud frames (90th percentile) against quiet frames (10th percentile).
+    pub snr_db: f32,
+    /// Level of the loud frames; roughly the speech level.
+    pub speech_db: f32,
+    /// Input lost because processing fell behind and the capture buffer was full.
+    pub dropped_seconds: f32,
+}
+
+impl InputStats {
+    /// Human-readable problems with the input, if any.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.seconds <= 0.0 {
+            return warnings;
+        }
+        if self.peak_db < SILENT_PEAK_DB {
+            warnings.push(format!(
+                "input was effectively silent (peak {:.0} dBFS); check that the microphone is not muted",
+                self.peak_db
+            ));
+        } else if self.speech_db < LOW_LEVEL_DB {
+            warnings.push(format!(
+                "input level is very low (speech around {:.0} dBFS); raise the microphone gain",
+                self.speech_db
+            ));
+        }
+        if self.clipped_ratio > CLIPPED_RATIO_WARNING {
+            warnings.push(format!(
+                "input is clipping ({:.1}% of samples at full scale); lower the microphone gain",
+                self.clipped_ratio * 100.0
+            ));
+        }
+        if self.dropped_seconds > 0.0 {
+            warnings.push(format!(
+                "{:.0} ms of input was dropped because processing fell behind",
+                self.dropped_seconds * 1_000.0
+            ));
+        }
+        warnings
+    }
+}
+
+impl std::fmt::Display for InputStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.1}s peak {:.1} dBFS, rms {:.1} dBFS, speech {:.1} dBFS, clipped {:.3}%, dc {:+.4}, snr {:.1} dB, dropped {:.3}s",
+            self.seconds,
+            self.peak_db,
+            self.rms_db,
+            self.speech_db,
+            self.clipped_ratio * 100.0,
+            self.dc_offset,
+            self.snr_db,
+            self.dropped_seconds
+        )
+    }
+}
+
+/// Accumulates `InputStats` over a stream of mono samples.
+pub struct StatsCollector {
+    sample_rate: u32,
+    frame_len: usize,
+    frame: Vec<f32>,
+    frame_levels: Vec<f32>,
+    samples: u64,
+    seconds: f64,
+    sum: f64,
+    sum_sq: f64,
+    peak: f32,
+    clipped: u64,
+    dropped_seconds: f64,
+}
+
+impl StatsCollector {
+    pub fn new(sample_rate: u32) -> Self {
+        let frame_len = frame_len(sample_rate);
+        Self {
+            sample_rate,
+            frame_len,
+            frame: Vec::with_capacity(frame_len),
+            frame_levels: Vec::new(),
+            samples: 0,
+            seconds: 0.0,
+            sum: 0.0,
+            sum_sq: 0.0,
+            peak: 0.0,
+            clipped: 0,
+            dropped_seconds: 0.0,
+        }
+    }
+
+    /// Following samples arrive at `sample_rate`; a partial frame is dropped.
+    pub fn set_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate;
+        self.frame_len = frame_len(sample_rate);
+        self.frame.clear();
+    }
+
+    pub fn push(&mut self, samples: &[f32]) {
+        self.seconds += samples.len() as f64 / self.sample_rate as f64;
+        for &s in samples {
+            self.samples += 1;
+            self.sum += s as f64;
+            self.sum_sq += (s * s) as f64;
+            self.peak = self.peak.max(s.abs());
+            if s.abs() >= CLIP_LEVEL {
+                self.clipped += 1;
+            }
+            self.frame.push(s);
+            if self.frame.len() == self.frame_len {
+                self.frame_levels.push(rms(&self.frame));
+                self.frame.clear();
+            }
+        }
+    }
+
+    /// Records `samples` at `sample_rate` that never reached the pipeline.
+    pub fn add_dropped(&mut self, samples: u64, sample_rate: u32) {
+        self.dropped_seconds += samples as f64 / sample_rate.max(1) as f64;
+    }
+
+    pub fn finish(mut self) -> InputStats {
+        if self.samples == 0 {
+            return InputStats {
+                seconds: 0.0,
+                peak_db: to_db(0.0),
+                rms_db: to_db(0.0),
+                clipped_ratio: 0.0,
+                dc_offset: 0.0,
+                snr_db: 0.0,
+                speech_db: to_db(0.0),
+                dropped_seconds: self.dropped_seconds as f32,
+            };
+        }
+
+        let n = self.samples as f64;
+        self.frame_levels.sort_by(f32::total_cmp);
+        let percentile = |p: f32| -> f32 {
+            if self.frame_levels.is_empty() {
+                return 0.0;
+            }
+            let i = ((self.frame_levels.len() - 1) as f32 * p).round() as usize;
+            self.frame_levels[i]
+        };
+        let noise = percentile(0.1);
+        let speech = percentile(0.9);
+
+        InputStats {
+            seconds: self.seconds as f32,
+            peak_db: to_db(self.peak),
+            rms_db: to_db((self.sum_sq / n).sqrt() as f32),
+            clipped_ratio: (self.clipped as f64 / n) as f32,
+            dc_offset: (self.sum / n) as f32,
+            snr_db: to_db(speech) - to_db(noise),
+            speech_db: to_db(speech),
+            dropped_seconds: self.dropped_seconds as f32,
+        }
+    }
+}
+
+/// Live input level over the last meter window, linear full scale.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioLevel {
+    pub rms: f32,
+    pub peak: f32,
+}
+
+/// Turns a sample stream into one `AudioLevel` per 50 ms of audio.
+pub struct LevelMeter {
+    window_len: usize,
+    count: usize,
+    sum_sq: f32,
+    peak: f32,
+}
+
+impl LevelMeter {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            window_len: window_len(sample_rate),
+            count: 0,
+            sum_sq: 0.0,
+            peak: 0.0,
+        }
+    }
+
+    pub fn set_rate(&mut self, sample_rate: u32) {
+        self.window_len = window_len(sample_rate);
+    }
+
+    /// Returns the level of the most recent window completed by `samples`.
+    pub fn push(&mut self, samples: &[f32]) -> Option<AudioLevel> {
+        let mut level = None;
+        for &s in samples {
+            self.sum_sq += s * s;
+            self.peak = self.peak.max(s.abs());
+            self.count += 1;
+            if self.count >= self.window_len {
+                level = Some(AudioLevel {
+                    rms: (self.sum_sq / self.count as f32).sqrt(),
+                    peak: self.peak,
+                });
+                self.count = 0;
+                self.sum_sq = 0.0;
+                self.peak = 0.0;
+            }
+        }
+        level
+    }
+}
+
+fn window_len(sample_rate: u32) -> usize {
+    (sample_rate as u64 * LEVEL_WINDOW_MS / 1_000).max(1) as usize
+}
+
+fn frame_len(sample_rate: u32) -> usize {
+    (sample_rate as u64 * FRAME_MS / 1_000).max(1) as usize
+}
+
+fn to_db(level: f32) -> f32 {
+    20.0 * level.max(LEVEL_FLOOR).log10()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16_000;
+
+    fn collect(samples: &[f32]) -> InputStats {
+        let mut stats = StatsCollector::new(RATE);
+        for chunk in samples.chunks(333) {
+            stats.push(chunk);
+        }
+        stats.finish()
+    }
+
+    fn tone(len: usize, amplitude: f32) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / RATE as f32).sin())
+            .collect()
+    }
+
+    #[test]
+    fn measures_level_dc_and_snr() {
+        // One second of quiet floor, then one second of a tone riding on a DC offset.
+        let mut samples = vec![0.001; RATE as usize];
+        samples.extend(tone(RATE as usize, 0.5).iter().map(|s| s + 0.05));
+        let stats = collect(&samples);
+
+        assert!((stats.seconds - 2.0).abs() < 1e-3);
+        assert!((stats.peak_db - 20.0 * 0.55f32.log10()).abs() < 0.1);
+        assert!((stats.dc_offset - 0.0255).abs() < 1e-3);
+        assert_eq!(stats.clipped_ratio, 0.0);
+        assert!((stats.snr_db - 20.0 * (0.3578f32 / 0.001).log10()).abs() < 1.0, "snr {}", stats.snr_db);
+        assert!(stats.warnings().is_empty(), "{:?}", stats.warnings());
+    }
+
+    #[test]
+    fn warns_about_muted_input() {
+        let stats = collect(&vec![0.0; RATE as usize]);
+        assert_eq!(stats.peak_db, -120.0);
+        let warnings = stats.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("silent"));
+    }
+
+    #[test]
+    fn warns_about_low_level_and_clipping() {
+        let low = collect(&tone(RATE as usize, 0.005));
+        assert!(low.warnings()[0].contains("very low"));
+
+        let clipped: Vec<f32> = tone(RATE as usize, 3.0).iter().map(|s| s.clamp(-1.0, 1.0)).collect();
+        let stats = collect(&clipped);
+        assert!(stats.clipped_ratio > 0.5);
+        let warnings = stats.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("clipping"));
+    }
+
+    #[test]
+    fn level_meter_reports_every_50_ms() {
+        let mut meter = LevelMeter::new(RATE);
+        assert_eq!(meter.push(&[0.5; 799]), None);
+        let level = meter.push(&[-1.0]).unwrap();
+        assert!((level.rms - (0.25f32 * 799.0 / 800.0 + 1.0 / 800.0).sqrt()).abs() < 1e-6);
+        assert_eq!(level.peak, 1.0);
+
+        // Several windows in one buffer report only the latest.
+        let mut loud_then_quiet = vec![0.8; 800];
+        loud_then_quiet.extend([0.1; 800]);
+        let level = meter.push(&loud_then_quiet).unwrap();
+        assert!((level.rms - 0.1).abs() < 1e-6);
+        assert_eq!(level.peak, 0.1);
+    }
+
+    #[test]
+    fn reports_dropped_input() {
+        let mut stats = StatsCollector::new(RATE);
+        stats.push(&tone(RATE as usize, 0.5));
+        stats.add_dropped(4_800, 48_000);
+        let stats = stats.finish();
+        assert!((stats.dropped_seconds - 0.1).abs() < 1e-6);
+        assert_eq!(stats.warnings(), vec!["100 ms of input was dropped because processing fell behind"]);
+    }
+
+    #[test]
+    fn empty_input_has_no_warnings() {
+        let stats = StatsCollector::new(RATE).finish();
+        assert_eq!(stats.seconds, 0.0);
+        assert!(stats.warnings().is_empty());
+    }
+}
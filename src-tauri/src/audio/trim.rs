@@ -0,0 +1,152 @@
+use super::vad::{Vad, MIN_SPEECH_FRAMES};
+
+/// Streaming trimmer that drops leading and trailing non-speech while keeping
+/// `padding` samples of context around the detected speech.
+///
+/// Audio is held back until it is known whether speech follows it. If no
+/// speech is ever detected the recording is passed through untouched, so a
+/// quiet microphone never produces an empty upload.
+pub struct SilenceTrimmer {
+    vad: Vad,
+    padding: usize,
+    pending: Vec<f32>,
+    held: Vec<f32>,
+    speech_seen: bool,
+    speech_run: u32,
+}
+
+impl SilenceTrimmer {
+    pub fn new(sample_rate: u32, threshold_db: f32, padding_ms: u64) -> Self {
+        let vad = Vad::new(sample_rate, threshold_db);
+        let frame_len = vad.frame_len();
+        Self {
+            vad,
+            padding: (sample_rate as u64 * padding_ms / 1_000) as usize,
+            pending: Vec::with_capacity(frame_len),
+            held: Vec::new(),
+            speech_seen: false,
+            speech_run: 0,
+        }
+    }
+
+    /// Feeds samples and returns the ones that are final.
+    pub fn push(&mut self, samples: &[f32]) -> Vec<f32> {
+        let mut out = Vec::new();
+        let frame_len = self.vad.frame_len();
+        for &sample in samples {
+            self.pending.push(sample);
+            if self.pending.len() == frame_len {
+                let frame = std::mem::replace(&mut self.pending, Vec::with_capacity(frame_len));
+                self.push_frame(&frame, &mut out);
+            }
+        }
+        out
+    }
+
+    /// Returns the remaining samples once the recording has ended.
+    pub fn finish(mut self) -> Vec<f32> {
+        self.held.append(&mut self.pending);
+        if self.speech_seen {
+            self.held.truncate(self.padding);
+        }
+        self.held
+    }
+
+    fn push_frame(&mut self, frame: &[f32], out: &mut Vec<f32>) {
+        self.held.extend_from_slice(frame);
+        if !self.vad.is_speech(frame) {
+            self.speech_run = 0;
+            return;
+        }
+
+        self.speech_run += 1;
+        if self.speech_run < MIN_SPEECH_FRAMES {
+            return;
+        }
+
+        if !self.speech_seen {
+            // Keep only the padding before the start of the first speech run.
+            let run_start = self.held.len() - self.speech_run as usize * frame.len();
+            let start = run_start.saturating_sub(self.padding);
+            out.extend_from_slice(&self.held[start..]);
+            self.held.clear();
+            self.speech_seen = true;
+            return;
+        }
+
+        out.append(&mut self.held);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16_000;
+
+    fn silence(ms: usize) -> Vec<f32> {
+        vec![0.0; ms * RATE as usize / 1_000]
+    }
+
+    fn tone(ms: usize) -> Vec<f32> {
+        (0..ms * RATE as usize / 1_000)
+            .map(|i| 0.5 * (i as f32 * 2.0 * std::f32::consts::PI * 300.0 / RATE as f32).sin())
+            .collect()
+    }
+
+    fn run(mut trimmer: SilenceTrimmer, input: &[f32], chunk: usize) -> Vec<f32> {
+        let mut out = Vec::new();
+        for c in input.chunks(chunk) {
+            out.extend(trimmer.push(c));
+        }
+        out.extend(trimmer.finish());
+        out
+    }
+
+    #[test]
+    fn trims_leading_and_trailing_silence_with_padding() {
+        let mut input = silence(1_000);
+        input.extend(tone(500));
+        input.extend(silence(1_000));
+
+        let out = run(SilenceTrimmer::new(RATE, -40.0, 100), &input, 441);
+
+        // 100 ms padding + 500 ms speech + 100 ms padding.
+        assert_eq!(out.len(), 700 * RATE as usize / 1_000);
+        let speech_start = 100 * RATE as usize / 1_000;
+        assert_eq!(&out[speech_start..speech_start + 16], &input[16_000..16_016]);
+    }
+
+    #[test]
+    fn keeps_internal_pauses() {
+        let mut input = silence(300);
+        input.extend(tone(200));
+        input.extend(silence(800));
+        input.extend(tone(200));
+        input.extend(silence(300));
+
+        let out = run(SilenceTrimmer::new(RATE, -40.0, 0), &input, 160);
+        assert_eq!(out.len(), 1_200 * RATE as usize / 1_000);
+    }
+
+    #[test]
+    fn drops_key_clicks_outside_speech() {
+        let click = vec![0.9; 320];
+        let mut input = click.clone();
+        input.extend(silence(500));
+        input.extend(tone(300));
+        input.extend(silence(500));
+        input.extend(click);
+        input.extend(silence(200));
+
+        let out = run(SilenceTrimmer::new(RATE, -40.0, 0), &input, 1_024);
+        assert_eq!(out.len(), 300 * RATE as usize / 1_000);
+    }
+
+    #[test]
+    fn passes_through_when_no_speech_is_detected() {
+        let input: Vec<f32> = (0..10_007).map(|i| if i % 2 == 0 { 0.001 } else { -0.001 }).collect();
+        let out = run(SilenceTrimmer::new(RATE, -40.0, 100), &input, 512);
+        assert_eq!(out, input);
+    }
+}
@@ -0,0 +1,187 @@
+/// Length of one VAD analysis frame.
+pub const FRAME_MS: u64 = 20;
+
+/// Number of consecutive speech frames required before a recording counts as
+/// containing speech. Keeps a single key click from arming the endpoint.
+pub const MIN_SPEECH_FRAMES: u32 = 5;
+
+/// Energy-based voice activity detector working on fixed 20 ms frames.
+pub struct Vad {
+    frame_len: usize,
+    threshold: f32,
+    pending: Vec<f32>,
+}
+
+impl Vad {
+    pub fn new(sample_rate: u32, threshold_db: f32) -> Self {
+        let frame_len = (sample_rate as u64 * FRAME_MS / 1_000).max(1) as usize;
+        Self {
+            frame_len,
+            threshold: db_to_linear(threshold_db),
+            pending: Vec::with_capacity(frame_len),
+        }
+    }
+
+    pub fn frame_len(&self) -> usize {
+        self.frame_len
+    }
+
+    pub fn is_speech(&self, frame: &[f32]) -> bool {
+        rms(frame) >= self.threshold
+    }
+
+    /// Feeds samples and returns one speech decision per completed frame.
+    pub fn push(&mut self, samples: &[f32]) -> Vec<bool> {
+        let mut decisions = Vec::new();
+        for &sample in samples {
+            self.pending.push(sample);
+            if self.pending.len() == self.frame_len {
+                decisions.push(self.is_speech(&self.pending));
+                self.pending.clear();
+            }
+        }
+        decisions
+    }
+}
+
+/// Tracks VAD decisions and fires once speech has been followed by enough
+/// trailing silence.
+pub struct EndpointDetector {
+    silence_frames_needed: u32,
+    speech_run: u32,
+    speech_seen: bool,
+    silence_run: u32,
+    fired: bool,
+}
+
+impl EndpointDetector {
+    pub fn new(silence_ms: u64) -> Self {
+        Self {
+            silence_frames_needed: silence_ms.div_ceil(FRAME_MS).max(1) as u32,
+            speech_run: 0,
+            speech_seen: false,
+            silence_run: 0,
+            fired: false,
+        }
+    }
+
+    /// Starts as if speech had already been heard, so it fires on the first
+    /// `silence_ms` of silence. Used where the caller knows speech preceded it.
+    pub fn armed(silence_ms: u64) -> Self {
+        Self {
+            speech_seen: true,
+            ..Self::new(silence_ms)
+        }
+    }
+
+    /// Returns `true` exactly once, on the frame that completes the trailing
+    /// silence after speech.
+    pub fn update(&mut self, is_speech: bool) -> bool {
+        if self.fired {
+            return false;
+        }
+
+        if is_speech {
+            self.speech_run += 1;
+            self.silence_run = 0;
+            if self.speech_run >= MIN_SPEECH_FRAMES {
+                self.speech_seen = true;
+            }
+            return false;
+        }
+
+        self.speech_run = 0;
+        if !self.speech_seen {
+            return false;
+        }
+
+        self.silence_run += 1;
+        if self.silence_run >= self.silence_frames_needed {
+            self.fired = true;
+            return true;
+        }
+        false
+    }
+}
+
+pub fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum: f32 = samples.iter().map(|s| s * s).sum();
+    (sum / samples.len() as f32).sqrt()
+}
+
+pub fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(len: usize, amplitude: f32) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (i as f32 * 2.0 * std::f32::consts::PI * 440.0 / 16_000.0).sin())
+            .collect()
+    }
+
+    #[test]
+    fn classifies_frames_by_energy() {
+        let mut vad = Vad::new(16_000, -40.0);
+        assert_eq!(vad.frame_len(), 320);
+
+        let mut input = vec![0.0; 320];
+        input.extend(tone(320, 0.5));
+        input.extend(vec![0.001; 100]);
+
+        assert_eq!(vad.push(&input), vec![false, true]);
+        // The trailing 100 samples stay pending until the frame completes.
+        assert_eq!(vad.push(&[0.001; 220]), vec![false]);
+    }
+
+    #[test]
+    fn endpoint_fires_after_trailing_silence_following_speech() {
+        let mut ep = EndpointDetector::new(100);
+
+        // Leading silence never fires.
+        for _ in 0..50 {
+            assert!(!ep.update(false));
+        }
+        for _ in 0..MIN_SPEECH_FRAMES {
+            assert!(!ep.update(true));
+        }
+        for _ in 0..4 {
+            assert!(!ep.update(false));
+        }
+        assert!(ep.update(false));
+        assert!(!ep.update(false));
+    }
+
+    #[test]
+    fn endpoint_ignores_short_clicks_and_resets_on_speech() {
+        let mut ep = EndpointDetector::new(40);
+        assert!(!ep.update(true));
+        for _ in 0..10 {
+            assert!(!ep.update(false));
+        }
+
+        for _ in 0..MIN_SPEECH_FRAMES {
+            ep.update(true);
+        }
+        assert!(!ep.update(false));
+        assert!(!ep.update(true));
+        assert!(!ep.update(false));
+        assert!(ep.update(false));
+    }
+
+    #[test]
+    fn armed_endpoint_fires_on_silence_without_prior_speech() {
+        let mut ep = EndpointDetector::armed(60);
+        assert!(!ep.update(false));
+        assert!(!ep.update(true));
+        assert!(!ep.update(false));
+        assert!(!ep.update(false));
+        assert!(ep.update(false));
+    }
+}
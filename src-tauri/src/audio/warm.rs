@@ -0,0 +1,331 @@
+use super::ring::RingWriter;
+use super::source::SourceSink;
+use super::{build_input, device};
+use crate::config;
+use cpal::traits::StreamTrait;
+use crossbeam_channel::{RecvTimeoutError, Sender};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How often the warm input checks for the idle timeout and a lost device.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Requests that can wait for the audio callback at once; there is at most
+/// one attach and one detach in flight.
+const REQUEST_CAPACITY: usize = 4;
+
+/// An input stream kept open between recordings.
+///
+/// While idle it remembers the last `preRoll.durationMs` of audio. A recording
+/// attaches to it and receives that pre-roll followed by the live stream, with
+/// nothing lost in between.
+#[derive(Clone)]
+pub struct WarmInput {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    device_name: String,
+    sample_rate: u32,
+    control: Arc<Mutex<Control>>,
+    closed: Arc<AtomicBool>,
+    stop: Sender<()>,
+}
+
+/// The warm input's state outside the audio callback, which it reaches only
+/// through lock-free queues.
+struct Control {
+    requests: rtrb::Producer<Request>,
+    /// Rings the callback let go of, freed here rather than on the audio thread.
+    retired: rtrb::Consumer<RingWriter>,
+    /// Set while a recording is attached: where its stream errors go.
+    errors: Option<(Sender<(u32, cpal::StreamError)>, u32)>,
+    idle_since: Instant,
+}
+
+impl Control {
+    fn send(&mut self, request: Request) -> bool {
+        while self.retired.pop().is_ok() {}
+        self.requests.push(request).is_ok()
+    }
+}
+
+enum Request {
+    /// Starts keeping a pre-roll, once the stream's rate is known.
+    PreRoll(PreRoll),
+    /// Sends the pre-roll and then the live stream to a recording's ring.
+    Attach(RingWriter),
+    Detach,
+}
+
+/// The audio callback's state. It never locks, and allocates nothing: rings
+/// and the pre-roll buffer arrive preallocated through `requests`.
+struct Feed {
+    requests: rtrb::Consumer<Request>,
+    retired: rtrb::Producer<RingWriter>,
+    pre_roll: PreRoll,
+    ring: Option<RingWriter>,
+}
+
+impl Feed {
+    fn push(&mut self, mono: &[f32]) {
+        while let Ok(request) = self.requests.pop() {
+            match request {
+                Request::PreRoll(pre_roll) => self.pre_roll = pre_roll,
+                Request::Attach(mut ring) => {
+                    let (first, second) = self.pre_roll.samples.as_slices();
+                    ring.push(first);
+                    ring.push(second);
+                    self.pre_roll.samples.clear();
+                    self.ring = Some(ring);
+                }
+                Request::Detach => {
+                    if let Some(ring) = self.ring.take() {
+                        // `Control` empties `retired` before every request, so this fits.
+                        let _ = self.retired.push(ring);
+                    }
+                }
+            }
+        }
+        match self.ring.as_mut() {
+            Some(ring) => ring.push(mono),
+            None => self.pre_roll.push(mono),
+        }
+    }
+}
+
+/// The two ends of the queues between `Control` and `Feed`.
+fn feed() -> (Control, Feed) {
+    let (requests, requests_rx) = rtrb::RingBuffer::new(REQUEST_CAPACITY);
+    let (retired_tx, retired) = rtrb::RingBuffer::new(REQUEST_CAPACITY);
+    let control = Control {
+        requests,
+        retired,
+        errors: None,
+        idle_since: Instant::now(),
+    };
+    let feed = Feed {
+        requests: requests_rx,
+        retired: retired_tx,
+        pre_roll: PreRoll::new(0),
+        ring: None,
+    };
+    (control, feed)
+}
+
+impl WarmInput {
+    /// Opens the configured input device and starts filling the pre-roll.
+    /// `on_close` runs once the stream has been released, whether by `close`,
+    /// the idle timeout or the device going away.
+    pub fn open(cfg: &config::RecordingConfig, on_close: impl FnOnce() + Send + 'static) -> Result<Self, String> {
+        let cfg = cfg.clone();
+        let (ready_tx, ready_rx) = crossbeam_channel::bounded(1);
+
+        std::thread::spawn(move || {
+            let (stop_tx, stop_rx) = crossbeam_channel::bounded(1);
+            let (stream, warm) = match open_stream(&cfg, stop_tx) {
+                Ok(opened) => opened,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+            let _ = ready_tx.send(Ok(warm.clone()));
+
+            let idle_release = Duration::from_secs(cfg.pre_roll.idle_release_seconds.max(1));
+            while let Err(RecvTimeoutError::Timeout) = stop_rx.recv_timeout(POLL_INTERVAL) {
+                if !warm.is_open() {
+                    break;
+                }
+                let idle = warm
+                    .shared
+                    .control
+                    .lock()
+                    .map_or(true, |control| control.errors.is_none() && control.idle_since.elapsed() >= idle_release);
+                if idle {
+                    break;
+                }
+            }
+
+            warm.shared.closed.store(true, Ordering::SeqCst);
+            drop(stream);
+            on_close();
+        });
+
+        ready_rx
+            .recv()
+            .map_err(|_| "warm input thread exited during startup".to_string())?
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.shared.closed.load(Ordering::SeqCst)
+    }
+
+    /// Releases the input device. An attached recording loses its input.
+    pub fn close(&self) {
+        let _ = self.shared.stop.try_send(());
+    }
+
+    /// Hands the pre-roll and then the live stream to `sink`. Returns `None`
+    /// when the stream is closed or another recording is already attached.
+    pub(super) fn attach(&self, sink: &SourceSink) -> Option<Attachment> {
+        if !self.is_open() {
+            return None;
+        }
+        let (ring, reader) = sink.ring();
+        let mut control = self.shared.control.lock().ok()?;
+        if control.errors.is_some() || !control.send(Request::Attach(ring)) {
+            return None;
+        }
+        control.errors = Some((sink.errors.clone(), sink.generation));
+        drop(control);
+        sink.start(reader, self.shared.sample_rate);
+        Some(Attachment {
+            shared: Arc::clone(&self.shared),
+        })
+    }
+}
+
+/// A recording's hold on a warm input; dropping it returns the input to
+/// filling its pre-roll.
+pub(super) struct Attachment {
+    shared: Arc<Shared>,
+}
+
+impl Attachment {
+    pub fn device_name(&self) -> &str {
+        &self.shared.device_name
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.shared.sample_rate
+    }
+}
+
+impl Drop for Attachment {
+    fn drop(&mut self) {
+        if let Ok(mut control) = self.shared.control.lock() {
+            control.send(Request::Detach);
+            control.errors = None;
+            control.idle_since = Instant::now();
+        }
+    }
+}
+
+fn open_stream(cfg: &config::RecordingConfig, stop: Sender<()>) -> Result<(cpal::Stream, WarmInput), String> {
+    let host = cpal::default_host();
+    let selected = device::select_input_device(&host, cfg.input_device.as_deref())?;
+
+    let (control, mut feed) = feed();
+    let control = Arc::new(Mutex::new(control));
+    let closed = Arc::new(AtomicBool::new(false));
+
+    let error_control = Arc::clone(&control);
+    let error_closed = Arc::clone(&closed);
+    let (stream, sample_rate) = build_input(
+        &selected.device,
+        cfg,
+        move |mono| feed.push(mono),
+        move |err| {
+            if matches!(err, cpal::StreamError::DeviceNotAvailable) {
+                error_closed.store(true, Ordering::SeqCst);
+            }
+            if let Ok(control) = error_control.lock() {
+                if let Some((errors, generation)) = control.errors.as_ref() {
+                    let _ = errors.send((*generation, err));
+                }
+            }
+        },
+    )?;
+
+    let capacity = (sample_rate as u64 * cfg.pre_roll.duration_ms / 1_000) as usize;
+    if let Ok(mut control) = control.lock() {
+        control.send(Request::PreRoll(PreRoll::new(capacity)));
+    }
+    stream
+        .play()
+        .map_err(|e| format!("failed to start input stream: {e}"))?;
+
+    let warm = WarmInput {
+        shared: Arc::new(Shared {
+            device_name: selected.name,
+            sample_rate,
+            control,
+            closed,
+            stop,
+        }),
+    };
+    Ok((stream, warm))
+}
+
+/// The most recent `capacity` samples.
+struct PreRoll {
+    samples: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl PreRoll {
+    fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    fn push(&mut self, samples: &[f32]) {
+        let samples = &samples[samples.len().saturating_sub(self.capacity)..];
+        let overflow = (self.samples.len() + samples.len()).saturating_sub(self.capacity);
+        self.samples.drain(..overflow);
+        self.samples.extend(samples);
+    }
+
+    #[cfg(test)]
+    fn take(&mut self) -> Vec<f32> {
+        self.samples.drain(..).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pre_roll_keeps_only_the_most_recent_samples() {
+        let mut pre_roll = PreRoll::new(4);
+        pre_roll.push(&[1.0, 2.0, 3.0]);
+        pre_roll.push(&[4.0, 5.0]);
+        assert_eq!(pre_roll.take(), vec![2.0, 3.0, 4.0, 5.0]);
+        assert!(pre_roll.take().is_empty());
+
+        pre_roll.push(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(pre_roll.take(), vec![3.0, 4.0, 5.0, 6.0]);
+
+        let mut disabled = PreRoll::new(0);
+        disabled.push(&[1.0]);
+        assert!(disabled.take().is_empty());
+    }
+
+    #[test]
+    fn feed_hands_over_the_pre_roll_then_the_live_stream() {
+        let (mut control, mut feed) = feed();
+        feed.push(&[9.0]);
+        assert!(control.send(Request::PreRoll(PreRoll::new(3))));
+        feed.push(&[1.0, 2.0, 3.0, 4.0]);
+
+        let (ring, mut reader) = crate::audio::ring::ring(16);
+        assert!(control.send(Request::Attach(ring)));
+        feed.push(&[5.0, 6.0]);
+        let mut samples = Vec::new();
+        reader.read(&mut samples);
+        assert_eq!(samples, vec![2.0, 3.0, 4.0, 5.0, 6.0]);
+
+        assert!(control.send(Request::Detach));
+        feed.push(&[7.0]);
+        samples.clear();
+        reader.read(&mut samples);
+        assert!(samples.is_empty());
+        assert_eq!(feed.pre_roll.take(), vec![7.0]);
+        assert!(control.retired.pop().is_ok());
+    }
+}
@@ -1,13 +1,44 @@
+use crate::audio::{self, RecordedAudio};
 use crate::config;
 use reqwest::multipart;
-use std::path::Path;
+
+/// Replaces the Azure service, e.g. on CI: `stub[:<text>]` answers every
+/// request with `<text>` without touching the network.
+const TRANSCRIBER_ENV: &str = "VOICEDICTATION_TRANSCRIBER";
+/// What `stub` alone answers with.
+const STUB_TEXT: &str = "stub transcript";
+
+/// The largest file the transcription endpoint accepts.
+const MAX_UPLOAD_BYTES: usize = 25 * 1024 * 1024;
+
+/// The longest recording that fits in one upload, about 13.6 minutes.
+pub fn max_upload_seconds() -> f64 {
+    MAX_UPLOAD_BYTES as f64 / audio::WAV_BYTES_PER_SECOND as f64
+}
 
 #[derive(Debug, serde::Deserialize)]
 struct TranscriptionResponse {
     text: String,
 }
 
-pub async fn transcribe_wav(path: &Path, cfg: &config::Config) -> Result<String, String> {
+/// The stub transcriber's answer when a `TRANSCRIBER_ENV` value selects it.
+fn stub_text(spec: Option<&str>) -> Result<Option<String>, String> {
+    match spec.map(str::trim) {
+        None | Some("") | Some("azure") => Ok(None),
+        Some("stub") => Ok(Some(STUB_TEXT.to_string())),
+        Some(spec) => match spec.strip_prefix("stub:") {
+            Some(text) => Ok(Some(text.to_string())),
+            None => Err(format!(
+                "{TRANSCRIBER_ENV}: expected \"azure\" or \"stub[:<text>]\", got \"{spec}\""
+            )),
+        },
+    }
+}
+
+pub async fn transcribe_wav(audio: &RecordedAudio, cfg: &config::Config) -> Result<String, String> {
+    if let Some(text) = stub_text(std::env::var(TRANSCRIBER_ENV).ok().as_deref())? {
+        return Ok(text);
+    }
     let api_key = cfg.azure.api_key.trim();
     if api_key.is_empty() {
         return Err("Azure apiKey is empty".to_string());
@@ -30,8 +61,14 @@ pub async fn transcribe_wav(path: &Path, cfg: &config::Config) -> Result<String,
         "{endpoint}/openai/deployments/{deployment}/audio/transcriptions?api-version={api_version}"
     );
 
-    let wav_bytes = std::fs::read(path)
-        .map_err(|e| format!("failed to read wav {}: {e}", path.display()))?;
+    let wav_bytes = audio.wav_bytes()?;
+    if wav_bytes.len() > MAX_UPLOAD_BYTES {
+        return Err(format!(
+            "audio is {:.1} MB, over the {} MB upload limit",
+            wav_bytes.len() as f64 / (1024.0 * 1024.0),
+            MAX_UPLOAD_BYTES / (1024 * 1024)
+        ));
+    }
 
     let file_part = multipart::Part::bytes(wav_bytes)
         .file_name("recording.wav")
@@ -69,3 +106,17 @@ pub async fn transcribe_wav(path: &Path, cfg: &config::Config) -> Result<String,
 
     Ok(parsed.text)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_transcriber_specs() {
+        assert_eq!(stub_text(None), Ok(None));
+        assert_eq!(stub_text(Some("azure")), Ok(None));
+        assert_eq!(stub_text(Some("stub")), Ok(Some(STUB_TEXT.to_string())));
+        assert_eq!(stub_text(Some("stub:hello: world")), Ok(Some("hello: world".to_string())));
+        assert!(stub_text(Some("whisper")).is_err());
+    }
+}
@@ -27,6 +27,8 @@ pub struct AzureConfig {
 #[serde(default, rename_all = "camelCase")]
 pub struct HotkeyConfig {
     pub windows: String,
+    /// Pauses or resumes a toggle-mode recording on Windows; empty disables it.
+    pub pause_windows: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,6 +42,206 @@ pub struct ThresholdsConfig {
 #[serde(default, rename_all = "camelCase")]
 pub struct RecordingConfig {
     pub max_seconds: u64,
+    /// Continue past `maxSeconds` in a new segment instead of stopping.
+    pub rollover: RolloverConfig,
+    /// Insert text at every pause while a toggle-mode recording continues.
+    pub incremental: IncrementalConfig,
+    /// Seconds before a recording is cut off (`maxSeconds` without rollover,
+    /// or the memory cap) that a warning is sent; 0 disables it.
+    pub limit_warning_seconds: u64,
+    /// Input device name; `None` uses the system default.
+    pub input_device: Option<String>,
+    /// Move to another input device when the current one disappears mid-recording.
+    pub auto_switch_device: bool,
+    /// What recordings capture unless the one starting it picks otherwise.
+    pub capture: CaptureMode,
+    /// Device recorded in `CaptureMode::SystemAudio` and `CaptureMode::Meeting`;
+    /// `None` uses the default output (its monitor on Linux).
+    pub loopback_device: Option<String>,
+    pub meeting: MeetingConfig,
+    pub channel_mode: ChannelMode,
+    /// Zero-based channel used by `ChannelMode::Select`.
+    pub channel_index: u16,
+    pub resample_quality: ResampleQuality,
+    /// RNNoise-based noise suppression, run at 48 kHz before the final resample.
+    pub denoise: bool,
+    /// Keep the recording in memory instead of a temporary WAV file.
+    pub in_memory: bool,
+    /// Longest audio an in-memory recording holds; `None` uses `maxSeconds`.
+    pub memory_cap_seconds: Option<u64>,
+    /// Memory for captured audio waiting to be processed. Input arriving
+    /// while it is full is dropped and reported in the input statistics.
+    pub capture_buffer_kb: u64,
+    pub dsp: DspConfig,
+    pub vad: VadConfig,
+    pub trim: TrimConfig,
+    pub pre_roll: PreRollConfig,
+    pub tail: TailConfig,
+    pub source: SourceConfig,
+}
+
+impl RecordingConfig {
+    /// Whether the pre-roll input would be opened differently under `other`:
+    /// another device, channel selection, source or pre-roll setting.
+    pub fn warm_input_differs(&self, other: &Self) -> bool {
+        self.input_device != other.input_device
+            || self.channel_mode != other.channel_mode
+            || self.channel_index != other.channel_index
+            || self.source != other.source
+            || self.pre_roll != other.pre_roll
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CaptureMode {
+    /// The input device (`inputDevice`).
+    Microphone,
+    /// What is playing on an output, through a loopback or monitor device.
+    SystemAudio,
+    /// Both sides of a call: the input device and system audio, mixed (see `meeting`).
+    Meeting,
+}
+
+/// Long recordings are handed off for transcription and insertion in segments
+/// of at most `maxSeconds` while capture continues.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RolloverConfig {
+    pub enabled: bool,
+    /// A segment ends at the first pause in speech within this many seconds
+    /// before `maxSeconds`, so it is not split mid-sentence.
+    pub search_seconds: u64,
+    /// Silence that counts as a pause.
+    pub pause_ms: u64,
+}
+
+/// Each stretch of speech is handed off for transcription and insertion as
+/// soon as the speaker pauses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct IncrementalConfig {
+    pub enabled: bool,
+    /// Silence after speech that ends a stretch.
+    pub pause_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MeetingConfig {
+    /// Gain applied to the input device before mixing.
+    pub microphone_gain_db: f32,
+    /// Gain applied to system audio before mixing.
+    pub system_gain_db: f32,
+    /// Also keep both sources as a two-channel WAV (input device left, system
+    /// audio right) for later separation. It is written to a temporary folder
+    /// that is emptied at startup and when the next recording starts, and it
+    /// cannot be combined with `inMemory`.
+    pub keep_separate_channels: bool,
+}
+
+/// How multi-channel input is reduced to mono.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChannelMode {
+    /// Average all channels.
+    Average,
+    /// Use only `channelIndex`.
+    Select,
+    /// Follow the channel with the most energy.
+    Loudest,
+}
+
+/// Resampler used to convert the device rate to 16 kHz.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ResampleQuality {
+    /// Linear interpolation without anti-aliasing (cheapest).
+    Linear,
+    /// Short windowed-sinc filter.
+    Fast,
+    /// Long windowed-sinc filter.
+    High,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DspConfig {
+    /// Remove DC offset and low-frequency rumble below `high_pass_hz`.
+    pub high_pass: bool,
+    pub high_pass_hz: f32,
+    /// Automatic gain toward `target_db` RMS, boosting by at most `max_gain_db`.
+    pub normalize: bool,
+    pub target_db: f32,
+    pub max_gain_db: f32,
+    /// Soft-limit peaks instead of hard clipping.
+    pub limiter: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct VadConfig {
+    pub threshold_db: f32,
+    pub auto_stop: bool,
+    pub auto_stop_silence_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TrimConfig {
+    pub enabled: bool,
+    pub padding_ms: u64,
+}
+
+/// Keeps the microphone open while idle so recordings include the moments
+/// before the hotkey fired.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PreRollConfig {
+    pub enabled: bool,
+    /// Audio from before the recording started that is prepended to it.
+    pub duration_ms: u64,
+    /// Close the idle microphone after this long without a recording; it
+    /// reopens when the next recording ends.
+    pub idle_release_seconds: u64,
+}
+
+/// Capture that continues after a manual stop so the last word is not cut off.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TailConfig {
+    /// Longest time capture continues after the stop request; 0 disables it.
+    pub duration_ms: u64,
+    /// End the tail early once `silenceMs` of silence is heard.
+    pub end_on_silence: bool,
+    pub silence_ms: u64,
+}
+
+/// Where recordings take their audio from. The `VOICEDICTATION_AUDIO_SOURCE`
+/// environment variable overrides it (`device`, `tone[:<hz>]`, `file:<path>`
+/// or `fast-file:<path>`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SourceConfig {
+    pub kind: SourceKind,
+    /// Audio file played by `SourceKind::File`.
+    pub file_path: Option<String>,
+    /// Play `filePath` as fast as the recording takes it rather than in real
+    /// time; the silence after it still runs in real time.
+    pub fast_playback: bool,
+    /// Frequency of the `SourceKind::Tone` test signal.
+    pub tone_hz: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SourceKind {
+    /// An input device (see `inputDevice`).
+    Device,
+    /// `filePath` played back (in real time unless `fastPlayback`), followed by silence.
+    File,
+    /// A continuous sine tone, for machines without any audio hardware.
+    Tone,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +249,32 @@ pub struct RecordingConfig {
 pub struct InsertConfig {
     pub restore_clipboard: bool,
     pub postfix: InsertPostfix,
+    /// Keys pressed to paste on Linux. Terminals usually want `ctrlShiftV`.
+    pub paste_chord: PasteChord,
+    /// How keys reach the focused app on Linux.
+    pub linux_backend: LinuxInsertBackend,
+    /// Linux only: type the text as key presses instead of pasting it, which
+    /// leaves the clipboard alone. Needs the uinput backend, `wtype` or `ydotool`.
+    pub type_text: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LinuxInsertBackend {
+    /// XTest on X11 sessions, otherwise the same as `uinput`.
+    Auto,
+    /// XTest only; X11 sessions and XWayland apps.
+    Xtest,
+    /// A `/dev/uinput` virtual keyboard, falling back to `wtype` and then
+    /// `ydotool` when one is installed. Works on Wayland.
+    Uinput,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PasteChord {
+    CtrlV,
+    CtrlShiftV,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -89,6 +317,7 @@ impl Default for HotkeyConfig {
     fn default() -> Self {
         Self {
             windows: "RightCtrl".to_string(),
+            pause_windows: "Ctrl+Alt+P".to_string(),
         }
     }
 }
@@ -104,7 +333,122 @@ impl Default for ThresholdsConfig {
 
 impl Default for RecordingConfig {
     fn default() -> Self {
-        Self { max_seconds: 120 }
+        Self {
+            max_seconds: 120,
+            rollover: RolloverConfig::default(),
+            incremental: IncrementalConfig::default(),
+            limit_warning_seconds: 10,
+            input_device: None,
+            auto_switch_device: true,
+            capture: CaptureMode::Microphone,
+            loopback_device: None,
+            meeting: MeetingConfig::default(),
+            channel_mode: ChannelMode::Average,
+            channel_index: 0,
+            resample_quality: ResampleQuality::High,
+            denoise: false,
+            in_memory: false,
+            memory_cap_seconds: None,
+            capture_buffer_kb: 1_024,
+            dsp: DspConfig::default(),
+            vad: VadConfig::default(),
+            trim: TrimConfig::default(),
+            pre_roll: PreRollConfig::default(),
+            tail: TailConfig::default(),
+            source: SourceConfig::default(),
+        }
+    }
+}
+
+impl Default for RolloverConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            search_seconds: 15,
+            pause_ms: 500,
+        }
+    }
+}
+
+impl Default for IncrementalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pause_ms: 800,
+        }
+    }
+}
+
+impl Default for MeetingConfig {
+    fn default() -> Self {
+        Self {
+            microphone_gain_db: 0.0,
+            system_gain_db: 0.0,
+            keep_separate_channels: false,
+        }
+    }
+}
+
+impl Default for DspConfig {
+    fn default() -> Self {
+        Self {
+            high_pass: true,
+            high_pass_hz: 80.0,
+            normalize: false,
+            target_db: -20.0,
+            max_gain_db: 20.0,
+            limiter: true,
+        }
+    }
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            threshold_db: -40.0,
+            auto_stop: false,
+            auto_stop_silence_ms: 1_500,
+        }
+    }
+}
+
+impl Default for TrimConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            padding_ms: 250,
+        }
+    }
+}
+
+impl Default for PreRollConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            duration_ms: 500,
+            idle_release_seconds: 300,
+        }
+    }
+}
+
+impl Default for TailConfig {
+    fn default() -> Self {
+        Self {
+            duration_ms: 400,
+            end_on_silence: true,
+            silence_ms: 160,
+        }
+    }
+}
+
+impl Default for SourceConfig {
+    fn default() -> Self {
+        Self {
+            kind: SourceKind::Device,
+            file_path: None,
+            fast_playback: false,
+            tone_hz: 440.0,
+        }
     }
 }
 
@@ -113,6 +457,9 @@ impl Default for InsertConfig {
         Self {
             restore_clipboard: true,
             postfix: InsertPostfix::None,
+            paste_chord: PasteChord::CtrlV,
+            linux_backend: LinuxInsertBackend::Auto,
+            type_text: false,
         }
     }
 }
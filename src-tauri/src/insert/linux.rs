@@ -0,0 +1,102 @@
+use super::tool::{self, Tool};
+use super::{uinput, x11};
+use crate::config::{LinuxInsertBackend, PasteChord};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayServer {
+    X11,
+    Wayland,
+}
+
+/// What to send to the focused app.
+#[derive(Debug, Clone, Copy)]
+pub enum Keystrokes<'a> {
+    Chord(PasteChord),
+    Text(&'a str),
+}
+
+/// The session's display server: `XDG_SESSION_TYPE` when it names one,
+/// otherwise whichever of `WAYLAND_DISPLAY` and `DISPLAY` is set. `var`
+/// looks up an environment variable.
+pub fn detect_display_server(var: impl Fn(&str) -> Option<String>) -> Result<DisplayServer, String> {
+    match var("XDG_SESSION_TYPE").as_deref() {
+        Some("x11") => return Ok(DisplayServer::X11),
+        Some("wayland") => return Ok(DisplayServer::Wayland),
+        _ => {}
+    }
+    let is_set = |name: &str| var(name).is_some_and(|value| !value.is_empty());
+    if is_set("WAYLAND_DISPLAY") {
+        Ok(DisplayServer::Wayland)
+    } else if is_set("DISPLAY") {
+        Ok(DisplayServer::X11)
+    } else {
+        Err("no display server found: neither DISPLAY nor WAYLAND_DISPLAY is set".to_string())
+    }
+}
+
+pub fn send(keys: Keystrokes, backend: LinuxInsertBackend) -> Result<(), String> {
+    match (backend, keys) {
+        (LinuxInsertBackend::Xtest, Keystrokes::Chord(chord)) => x11::paste(chord),
+        (LinuxInsertBackend::Xtest, Keystrokes::Text(_)) => {
+            Err("typing text needs the uinput backend; set insert.linuxBackend to auto or uinput".to_string())
+        }
+        (LinuxInsertBackend::Auto, Keystrokes::Chord(chord))
+            if detect_display_server(|name| std::env::var(name).ok())? == DisplayServer::X11 =>
+        {
+            x11::paste(chord)
+        }
+        _ => send_virtual(keys),
+    }
+}
+
+/// Sends through a `/dev/uinput` keyboard or, when that fails, through each
+/// installed `Tool` in turn.
+fn send_virtual(keys: Keystrokes) -> Result<(), String> {
+    let mut errors = match uinput::send(keys) {
+        Ok(()) => return Ok(()),
+        Err(e) => vec![e],
+    };
+    let path = std::env::var_os("PATH");
+    for tool in Tool::ALL {
+        let Some(program) = tool::find_program(tool.program(), path.as_deref()) else {
+            continue;
+        };
+        match tool.run(&program, keys) {
+            Ok(()) => return Ok(()),
+            Err(e) => errors.push(e),
+        }
+    }
+    if errors.len() == 1 {
+        errors.push("neither wtype nor ydotool is installed as a fallback".to_string());
+    }
+    Err(errors.join("; "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(vars: &[(&str, &str)]) -> Result<DisplayServer, String> {
+        detect_display_server(|name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn detects_the_display_server_from_the_environment() {
+        assert_eq!(detect(&[("DISPLAY", ":99")]), Ok(DisplayServer::X11));
+        assert_eq!(detect(&[("WAYLAND_DISPLAY", "wayland-0"), ("DISPLAY", ":0")]), Ok(DisplayServer::Wayland));
+        assert_eq!(
+            detect(&[("XDG_SESSION_TYPE", "x11"), ("WAYLAND_DISPLAY", "wayland-0")]),
+            Ok(DisplayServer::X11)
+        );
+        assert_eq!(
+            detect(&[("XDG_SESSION_TYPE", "wayland"), ("DISPLAY", ":0")]),
+            Ok(DisplayServer::Wayland)
+        );
+        assert_eq!(detect(&[("XDG_SESSION_TYPE", "tty"), ("DISPLAY", ":1")]), Ok(DisplayServer::X11));
+        assert!(detect(&[("DISPLAY", "")]).unwrap_err().contains("no display server"));
+    }
+}
@@ -0,0 +1,218 @@
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "linux")]
+mod tool;
+#[cfg(target_os = "linux")]
+mod uinput;
+#[cfg(target_os = "linux")]
+mod x11;
+
+use crate::config;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// Redirects insertion, e.g. on CI without a desktop: `file:<path>` appends
+/// the text to `<path>` instead of sending it to the focused app.
+const INSERT_ENV: &str = "VOICEDICTATION_INSERT";
+
+/// The clipboard `copy_text` last wrote, kept open so the text stays
+/// available.
+static COPIED: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+
+/// Keeps the clipboard open until it is restored: on X11 the text is only
+/// served for as long as this process holds it.
+pub struct ClipboardGuard {
+    clipboard: arboard::Clipboard,
+    original_text: Option<String>,
+}
+
+impl ClipboardGuard {
+    pub fn restore(mut self) -> Result<(), String> {
+        if let Some(text) = self.original_text {
+            self.clipboard
+                .set_text(text)
+                .map_err(|e| format!("failed to restore clipboard: {e}"))?;
+        }
+        Ok(())
+    }
+}
+
+pub fn set_clipboard_text_with_guard(text: &str, restore_original: bool) -> Result<ClipboardGuard, String> {
+    let mut clipboard =
+        arboard::Clipboard::new().map_err(|e| format!("failed to open clipboard: {e}"))?;
+
+    let original_text = if restore_original {
+        clipboard.get_text().ok()
+    } else {
+        None
+    };
+
+    clipboard
+        .set_text(text.to_string())
+        .map_err(|e| format!("failed to set clipboard text: {e}"))?;
+
+    Ok(ClipboardGuard { clipboard, original_text })
+}
+
+/// Leaves `text` on the clipboard. On Linux the text is served by this
+/// process, so the clipboard is kept open until the next copy.
+pub fn copy_text(text: &str) -> Result<(), String> {
+    let guard = set_clipboard_text_with_guard(text, false)?;
+    *COPIED.lock().map_err(|_| "clipboard mutex poisoned".to_string())? = Some(guard.clipboard);
+    Ok(())
+}
+
+/// `cfg.pasteChord` and `cfg.linuxBackend` are used on Linux; macOS pastes
+/// with Cmd+V and Windows with Ctrl+V.
+pub fn paste(cfg: &config::InsertConfig) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        let _ = cfg;
+        return macos_paste();
+    }
+    #[cfg(windows)]
+    {
+        let _ = cfg;
+        return windows_paste();
+    }
+    #[cfg(target_os = "linux")]
+    {
+        return linux::send(linux::Keystrokes::Chord(cfg.paste_chord), cfg.linux_backend);
+    }
+    #[cfg(not(any(target_os = "macos", windows, target_os = "linux")))]
+    {
+        let _ = cfg;
+        return Err("paste injection is not supported on this platform".to_string());
+    }
+}
+
+pub fn clipboard_paste_restore(text: &str, cfg: &config::InsertConfig) -> Result<(), String> {
+    let guard = set_clipboard_text_with_guard(text, cfg.restore_clipboard)?;
+    paste(cfg)?;
+    thread::sleep(Duration::from_millis(150));
+    guard.restore()?;
+    Ok(())
+}
+
+/// Inserts `text` into the focused app: typed with `insert.typeText` on
+/// Linux, pasted through the clipboard otherwise. `VOICEDICTATION_INSERT`
+/// can redirect it to a file.
+pub fn insert_text(text: &str, cfg: &config::InsertConfig) -> Result<(), String> {
+    if let Some(path) = insert_file(std::env::var(INSERT_ENV).ok().as_deref())? {
+        return append_to_file(&path, text);
+    }
+    #[cfg(target_os = "linux")]
+    if cfg.type_text {
+        return linux::send(linux::Keystrokes::Text(text), cfg.linux_backend);
+    }
+    clipboard_paste_restore(text, cfg)
+}
+
+/// The file named by an `INSERT_ENV` value, if it names one.
+fn insert_file(spec: Option<&str>) -> Result<Option<PathBuf>, String> {
+    match spec.map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(spec) => match spec.strip_prefix("file:") {
+            Some(path) if !path.is_empty() => Ok(Some(PathBuf::from(path))),
+            _ => Err(format!("{INSERT_ENV}: expected \"file:<path>\", got \"{spec}\"")),
+        },
+    }
+}
+
+fn append_to_file(path: &Path, text: &str) -> Result<(), String> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("failed to open {}: {e}", path.display()))?;
+    file.write_all(text.as_bytes())
+        .map_err(|e| format!("failed to write {}: {e}", path.display()))
+}
+
+#[cfg(target_os = "macos")]
+fn macos_paste() -> Result<(), String> {
+    use core_graphics::event::{CGEvent, CGEventFlags, CGEventTapLocation};
+    use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+
+    const KEY_V: u16 = 0x09;
+
+    let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+        .map_err(|e| format!("failed to create event source: {e:?}"))?;
+
+    let key_down = CGEvent::new_keyboard_event(source.clone(), KEY_V, true)
+        .map_err(|_| "failed to create keydown".to_string())?;
+    key_down.set_flags(CGEventFlags::CGEventFlagCommand);
+    key_down.post(CGEventTapLocation::HID);
+
+    let key_up = CGEvent::new_keyboard_event(source, KEY_V, false)
+        .map_err(|_| "failed to create keyup".to_string())?;
+    key_up.set_flags(CGEventFlags::CGEventFlagCommand);
+    key_up.post(CGEventTapLocation::HID);
+
+    Ok(())
+}
+
+#[cfg(windows)]
+fn windows_paste() -> Result<(), String> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, VIRTUAL_KEY,
+    };
+
+    const VK_CONTROL: VIRTUAL_KEY = VIRTUAL_KEY(0x11);
+    const VK_V: VIRTUAL_KEY = VIRTUAL_KEY(0x56);
+
+    unsafe fn key_input(vk: VIRTUAL_KEY, key_up: bool) -> INPUT {
+        let flags = if key_up { KEYEVENTF_KEYUP } else { Default::default() };
+        INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    wVk: vk,
+                    wScan: 0,
+                    dwFlags: flags,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        }
+    }
+
+    let inputs = [
+        unsafe { key_input(VK_CONTROL, false) },
+        unsafe { key_input(VK_V, false) },
+        unsafe { key_input(VK_V, true) },
+        unsafe { key_input(VK_CONTROL, true) },
+    ];
+
+    let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize != inputs.len() {
+        return Err(format!("SendInput sent {sent} events (expected {})", inputs.len()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redirects_insertion_to_a_file() {
+        assert_eq!(insert_file(None), Ok(None));
+        assert_eq!(insert_file(Some(" ")), Ok(None));
+        assert!(insert_file(Some("file:")).is_err());
+        assert!(insert_file(Some("stdout")).is_err());
+
+        let path = std::env::temp_dir().join(format!("voicedictation-insert-test-{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let spec = format!("file:{}", path.display());
+        assert_eq!(insert_file(Some(&spec)), Ok(Some(path.clone())));
+        append_to_file(&path, "one").unwrap();
+        append_to_file(&path, " two").unwrap();
+        let written = std::fs::read_to_string(&path);
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(written.unwrap(), "one two");
+    }
+}
@@ -0,0 +1,109 @@
+use super::linux::Keystrokes;
+use crate::config::PasteChord;
+use std::ffi::OsStr;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Command-line tools that send keys when `/dev/uinput` cannot be used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    /// Uses the Wayland virtual keyboard protocol (wlroots-based compositors).
+    Wtype,
+    /// Talks to a running `ydotoold`, which owns a uinput device itself.
+    Ydotool,
+}
+
+impl Tool {
+    /// In the order they are tried.
+    pub const ALL: [Tool; 2] = [Tool::Wtype, Tool::Ydotool];
+
+    pub fn program(self) -> &'static str {
+        match self {
+            Tool::Wtype => "wtype",
+            Tool::Ydotool => "ydotool",
+        }
+    }
+
+    fn args(self, keys: Keystrokes) -> Vec<String> {
+        let args: Vec<&str> = match (self, keys) {
+            (Tool::Wtype, Keystrokes::Chord(PasteChord::CtrlV)) => vec!["-M", "ctrl", "v", "-m", "ctrl"],
+            (Tool::Wtype, Keystrokes::Chord(PasteChord::CtrlShiftV)) => {
+                vec!["-M", "ctrl", "-M", "shift", "v", "-m", "shift", "-m", "ctrl"]
+            }
+            (Tool::Wtype, Keystrokes::Text(text)) => vec!["--", text],
+            // Linux key codes: 29 left Ctrl, 42 left Shift, 47 V.
+            (Tool::Ydotool, Keystrokes::Chord(PasteChord::CtrlV)) => vec!["key", "29:1", "47:1", "47:0", "29:0"],
+            (Tool::Ydotool, Keystrokes::Chord(PasteChord::CtrlShiftV)) => {
+                vec!["key", "29:1", "42:1", "47:1", "47:0", "42:0", "29:0"]
+            }
+            (Tool::Ydotool, Keystrokes::Text(text)) => vec!["type", "--", text],
+        };
+        args.into_iter().map(str::to_string).collect()
+    }
+
+    pub fn run(self, program: &Path, keys: Keystrokes) -> Result<(), String> {
+        let output = Command::new(program)
+            .args(self.args(keys))
+            .output()
+            .map_err(|e| format!("failed to run {}: {e}", program.display()))?;
+        if !output.status.success() {
+            return Err(format!(
+                "{} failed ({}): {}",
+                self.program(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The first executable named `name` in the directories of `path` (a `PATH` value).
+pub fn find_program(name: &str, path: Option<&OsStr>) -> Option<PathBuf> {
+    std::env::split_paths(path?)
+        .map(|dir| dir.join(name))
+        .find(|candidate| {
+            candidate
+                .metadata()
+                .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_tools_on_the_path_and_runs_them_with_their_arguments() {
+        let dir = std::env::temp_dir().join(format!("voicedictation-tool-test-{}", std::process::id()));
+        let empty = dir.join("empty");
+        let bin = dir.join("bin");
+        std::fs::create_dir_all(&empty).unwrap();
+        std::fs::create_dir_all(&bin).unwrap();
+        let args_file = dir.join("args");
+        let script = bin.join("wtype");
+        std::fs::write(&script, format!("#!/bin/sh\nprintf '%s\\n' \"$@\" > '{}'\n", args_file.display())).unwrap();
+        std::fs::write(bin.join("ydotool"), "not executable").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let path = std::env::join_paths([&empty, &bin]).unwrap();
+        assert_eq!(find_program("ydotool", Some(&path)), None);
+        let wtype = find_program("wtype", Some(&path)).unwrap();
+        assert_eq!(wtype, script);
+
+        Tool::Wtype.run(&wtype, Keystrokes::Chord(PasteChord::CtrlShiftV)).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&args_file).unwrap(),
+            "-M\nctrl\n-M\nshift\nv\n-m\nshift\n-m\nctrl\n"
+        );
+        Tool::Wtype.run(&wtype, Keystrokes::Text("-a b")).unwrap();
+        assert_eq!(std::fs::read_to_string(&args_file).unwrap(), "--\n-a b\n");
+        assert_eq!(
+            Tool::Ydotool.args(Keystrokes::Text("hi")),
+            ["type", "--", "hi"].map(String::from)
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,284 @@
+use super::linux::Keystrokes;
+use crate::config::PasteChord;
+use evdev::uinput::VirtualDevice;
+use evdev::{AttributeSet, InputEvent, KeyCode, KeyEvent};
+use std::fs::{self, OpenOptions};
+use std::io::ErrorKind;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+const UINPUT_PATH: &str = "/dev/uinput";
+/// Time the compositor needs to pick up a new device before its keys arrive.
+const SETTLE: Duration = Duration::from_millis(200);
+/// Gap between typed characters; some apps drop keys sent back to back.
+const KEY_DELAY: Duration = Duration::from_millis(5);
+
+/// Created on first use and kept, so later insertions skip `SETTLE`.
+static KEYBOARD: Mutex<Option<VirtualKeyboard>> = Mutex::new(None);
+
+const LETTERS: [KeyCode; 26] = [
+    KeyCode::KEY_A,
+    KeyCode::KEY_B,
+    KeyCode::KEY_C,
+    KeyCode::KEY_D,
+    KeyCode::KEY_E,
+    KeyCode::KEY_F,
+    KeyCode::KEY_G,
+    KeyCode::KEY_H,
+    KeyCode::KEY_I,
+    KeyCode::KEY_J,
+    KeyCode::KEY_K,
+    KeyCode::KEY_L,
+    KeyCode::KEY_M,
+    KeyCode::KEY_N,
+    KeyCode::KEY_O,
+    KeyCode::KEY_P,
+    KeyCode::KEY_Q,
+    KeyCode::KEY_R,
+    KeyCode::KEY_S,
+    KeyCode::KEY_T,
+    KeyCode::KEY_U,
+    KeyCode::KEY_V,
+    KeyCode::KEY_W,
+    KeyCode::KEY_X,
+    KeyCode::KEY_Y,
+    KeyCode::KEY_Z,
+];
+
+const DIGITS: [KeyCode; 10] = [
+    KeyCode::KEY_0,
+    KeyCode::KEY_1,
+    KeyCode::KEY_2,
+    KeyCode::KEY_3,
+    KeyCode::KEY_4,
+    KeyCode::KEY_5,
+    KeyCode::KEY_6,
+    KeyCode::KEY_7,
+    KeyCode::KEY_8,
+    KeyCode::KEY_9,
+];
+
+/// Unshifted and shifted characters of the remaining keys on a US layout.
+const SYMBOLS: [(KeyCode, char, char); 14] = [
+    (KeyCode::KEY_SPACE, ' ', ' '),
+    (KeyCode::KEY_ENTER, '\n', '\n'),
+    (KeyCode::KEY_TAB, '\t', '\t'),
+    (KeyCode::KEY_MINUS, '-', '_'),
+    (KeyCode::KEY_EQUAL, '=', '+'),
+    (KeyCode::KEY_LEFTBRACE, '[', '{'),
+    (KeyCode::KEY_RIGHTBRACE, ']', '}'),
+    (KeyCode::KEY_SEMICOLON, ';', ':'),
+    (KeyCode::KEY_APOSTROPHE, '\'', '"'),
+    (KeyCode::KEY_GRAVE, '`', '~'),
+    (KeyCode::KEY_BACKSLASH, '\\', '|'),
+    (KeyCode::KEY_COMMA, ',', '<'),
+    (KeyCode::KEY_DOT, '.', '>'),
+    (KeyCode::KEY_SLASH, '/', '?'),
+];
+
+/// Shifted characters of the digit keys, `)` through `(`.
+const SHIFTED_DIGITS: [char; 10] = [')', '!', '@', '#', '$', '%', '^', '&', '*', '('];
+
+/// The key that types `c` on a US layout, and whether Shift is needed.
+fn key_for_char(c: char) -> Option<(KeyCode, bool)> {
+    match c {
+        'a'..='z' => Some((LETTERS[c as usize - 'a' as usize], false)),
+        'A'..='Z' => Some((LETTERS[c as usize - 'A' as usize], true)),
+        '0'..='9' => Some((DIGITS[c as usize - '0' as usize], false)),
+        _ => SHIFTED_DIGITS
+            .iter()
+            .position(|&shifted| shifted == c)
+            .map(|digit| (DIGITS[digit], true))
+            .or_else(|| {
+                SYMBOLS.iter().find_map(|&(key, plain, shifted)| {
+                    if c == plain {
+                        Some((key, false))
+                    } else if c == shifted {
+                        Some((key, true))
+                    } else {
+                        None
+                    }
+                })
+            }),
+    }
+}
+
+fn chord_keys(chord: PasteChord) -> &'static [KeyCode] {
+    match chord {
+        PasteChord::CtrlV => &[KeyCode::KEY_LEFTCTRL, KeyCode::KEY_V],
+        PasteChord::CtrlShiftV => &[KeyCode::KEY_LEFTCTRL, KeyCode::KEY_LEFTSHIFT, KeyCode::KEY_V],
+    }
+}
+
+/// Checks that `path` exists and this user may write it, explaining how to
+/// grant access when not.
+fn check_access(path: &Path) -> Result<(), String> {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return Err(format!(
+                "{} does not exist; load the uinput kernel module with `sudo modprobe uinput`",
+                path.display()
+            ))
+        }
+        Err(e) => return Err(format!("failed to inspect {}: {e}", path.display())),
+    };
+    match OpenOptions::new().write(true).open(path) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::PermissionDenied => Err(permission_advice(
+            path,
+            metadata.mode(),
+            metadata.gid(),
+            in_group(metadata.gid()),
+        )),
+        Err(e) => Err(format!("failed to open {}: {e}", path.display())),
+    }
+}
+
+fn permission_advice(path: &Path, mode: u32, gid: u32, in_group: bool) -> String {
+    let reason = if mode & 0o020 == 0 {
+        format!("it is not writable by its group (mode {:o})", mode & 0o777)
+    } else if !in_group {
+        format!("this session is not in its group (gid {gid})")
+    } else {
+        "access was denied".to_string()
+    };
+    format!(
+        "no permission to write {}: {reason}. To allow it, add the udev rule \
+         `KERNEL==\"uinput\", GROUP=\"input\", MODE=\"0660\", OPTIONS+=\"static_node=uinput\"` \
+         to /etc/udev/rules.d/60-uinput.rules, add your user to the input group with \
+         `sudo usermod -aG input $USER`, then log out and back in",
+        path.display()
+    )
+}
+
+/// Whether the process has `gid` as its effective or a supplementary group.
+fn in_group(gid: u32) -> bool {
+    if unsafe { libc::getegid() } == gid {
+        return true;
+    }
+    let count = unsafe { libc::getgroups(0, std::ptr::null_mut()) };
+    if count <= 0 {
+        return false;
+    }
+    let mut groups = vec![0; count as usize];
+    let count = unsafe { libc::getgroups(count, groups.as_mut_ptr()) };
+    groups.truncate(count.max(0) as usize);
+    groups.contains(&gid)
+}
+
+/// A keyboard that exists only as a `/dev/uinput` device. The kernel treats
+/// its keys like a real keyboard's, so they reach Wayland and X11 apps alike,
+/// but they are read through the active layout: typed text assumes US.
+pub struct VirtualKeyboard {
+    device: VirtualDevice,
+}
+
+impl VirtualKeyboard {
+    pub fn create() -> Result<Self, String> {
+        check_access(Path::new(UINPUT_PATH))?;
+        let mut keys = AttributeSet::<KeyCode>::new();
+        for key in LETTERS.iter().chain(&DIGITS).chain(SYMBOLS.iter().map(|(key, _, _)| key)) {
+            keys.insert(*key);
+        }
+        keys.insert(KeyCode::KEY_LEFTCTRL);
+        keys.insert(KeyCode::KEY_LEFTSHIFT);
+
+        let device = VirtualDevice::builder()
+            .and_then(|builder| builder.name("VoiceDictation virtual keyboard").with_keys(&keys))
+            .and_then(|builder| builder.build())
+            .map_err(|e| format!("failed to create uinput keyboard: {e}"))?;
+        thread::sleep(SETTLE);
+        Ok(Self { device })
+    }
+
+    /// Presses `keys` in order, then releases them in reverse.
+    fn press_together(&mut self, keys: &[KeyCode]) -> Result<(), String> {
+        let down: Vec<InputEvent> = keys.iter().map(|&key| *KeyEvent::new(key, 1)).collect();
+        let up: Vec<InputEvent> = keys.iter().rev().map(|&key| *KeyEvent::new(key, 0)).collect();
+        self.device
+            .emit(&down)
+            .and_then(|()| self.device.emit(&up))
+            .map_err(|e| format!("failed to send uinput key events: {e}"))
+    }
+}
+
+/// The groups of keys pressed together to send `keys`, one per character of
+/// a `Keystrokes::Text`.
+fn key_groups(keys: Keystrokes) -> Result<Vec<Vec<KeyCode>>, String> {
+    match keys {
+        Keystrokes::Chord(chord) => Ok(vec![chord_keys(chord).to_vec()]),
+        Keystrokes::Text(text) => text
+            .chars()
+            .map(|c| match key_for_char(c) {
+                Some((key, true)) => Ok(vec![KeyCode::KEY_LEFTSHIFT, key]),
+                Some((key, false)) => Ok(vec![key]),
+                None => Err(format!("the uinput keyboard cannot type {c:?}; it only types US-layout characters")),
+            })
+            .collect(),
+    }
+}
+
+/// Sends `keys` through the shared virtual keyboard, creating it if needed.
+/// Nothing is sent unless every character of a `Keystrokes::Text` has a key.
+pub fn send(keys: Keystrokes) -> Result<(), String> {
+    let groups = key_groups(keys)?;
+    let mut keyboard = KEYBOARD.lock().map_err(|_| "virtual keyboard mutex poisoned".to_string())?;
+    let device = match keyboard.as_mut() {
+        Some(device) => device,
+        None => keyboard.insert(VirtualKeyboard::create()?),
+    };
+    for (index, group) in groups.iter().enumerate() {
+        if index > 0 {
+            thread::sleep(KEY_DELAY);
+        }
+        if let Err(e) = device.press_together(group) {
+            // Start over with a fresh device next time.
+            *keyboard = None;
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_us_layout_characters_to_keys() {
+        assert_eq!(
+            key_groups(Keystrokes::Text("Hi!")).unwrap(),
+            vec![
+                vec![KeyCode::KEY_LEFTSHIFT, KeyCode::KEY_H],
+                vec![KeyCode::KEY_I],
+                vec![KeyCode::KEY_LEFTSHIFT, KeyCode::KEY_1],
+            ]
+        );
+        assert!(key_groups(Keystrokes::Text("café")).unwrap_err().contains("'é'"));
+        assert_eq!(key_for_char('q'), Some((KeyCode::KEY_Q, false)));
+        assert_eq!(key_for_char('Z'), Some((KeyCode::KEY_Z, true)));
+        assert_eq!(key_for_char('7'), Some((KeyCode::KEY_7, false)));
+        assert_eq!(key_for_char('('), Some((KeyCode::KEY_9, true)));
+        assert_eq!(key_for_char(')'), Some((KeyCode::KEY_0, true)));
+        assert_eq!(key_for_char('?'), Some((KeyCode::KEY_SLASH, true)));
+        assert_eq!(key_for_char(' '), Some((KeyCode::KEY_SPACE, false)));
+        assert_eq!(key_for_char('é'), None);
+        assert_eq!(key_for_char('你'), None);
+    }
+
+    #[test]
+    fn explains_how_to_get_access_to_uinput() {
+        let missing = std::env::temp_dir().join(format!("voicedictation-no-uinput-{}", std::process::id()));
+        assert!(check_access(&missing).unwrap_err().contains("modprobe uinput"));
+
+        let path = Path::new(UINPUT_PATH);
+        let not_group_writable = permission_advice(path, 0o20600, 0, false);
+        assert!(not_group_writable.contains("not writable by its group (mode 600)"));
+        assert!(not_group_writable.contains("usermod -aG input"));
+        assert!(permission_advice(path, 0o20660, 104, false).contains("not in its group (gid 104)"));
+    }
+}
@@ -0,0 +1,149 @@
+use crate::config::PasteChord;
+use std::os::raw::{c_int, c_ulong};
+use std::ptr;
+use x11_dl::keysym;
+use x11_dl::xlib::{self, Xlib};
+use x11_dl::xtest::Xf86vmode as XTest;
+
+/// A connection to the X server that synthesizes key events with the XTEST
+/// extension. libX11 and libXtst are loaded at runtime, so a machine without
+/// them gets an error here rather than failing to start.
+pub struct Keyboard {
+    xlib: Xlib,
+    xtest: XTest,
+    display: *mut xlib::Display,
+    name: String,
+}
+
+impl Keyboard {
+    /// Connects to the display in `DISPLAY`.
+    pub fn open() -> Result<Self, String> {
+        let xlib = Xlib::open().map_err(|e| format!("failed to load libX11: {e}"))?;
+        let xtest = XTest::open().map_err(|e| format!("failed to load libXtst: {e}"))?;
+        let name = std::env::var("DISPLAY").unwrap_or_default();
+
+        let display = unsafe { (xlib.XOpenDisplay)(ptr::null()) };
+        if display.is_null() {
+            return Err(format!("failed to open X display \"{name}\""));
+        }
+        let keyboard = Self { xlib, xtest, display, name };
+
+        let (mut event_base, mut error_base, mut major, mut minor): (c_int, c_int, c_int, c_int) = (0, 0, 0, 0);
+        let supported = unsafe {
+            (keyboard.xtest.XTestQueryExtension)(keyboard.display, &mut event_base, &mut error_base, &mut major, &mut minor)
+        };
+        if supported == 0 {
+            return Err(format!("X display \"{}\" does not support the XTEST extension", keyboard.name));
+        }
+        Ok(keyboard)
+    }
+
+    /// Presses the keys of `chord` in order and releases them in reverse.
+    /// Every key that went down is released even when sending fails, so no
+    /// modifier is left held; the first error is returned.
+    pub fn send_chord(&self, chord: PasteChord) -> Result<(), String> {
+        let keys = self.chord_keycodes(chord)?;
+        let mut result = Ok(());
+        let mut pressed = 0;
+        for &key in &keys {
+            if let Err(e) = self.send_key(key, true) {
+                result = Err(e);
+                break;
+            }
+            pressed += 1;
+        }
+        for &key in keys[..pressed].iter().rev() {
+            let released = self.send_key(key, false);
+            if result.is_ok() {
+                result = released;
+            }
+        }
+        self.sync();
+        result
+    }
+
+    fn chord_keycodes(&self, chord: PasteChord) -> Result<Vec<u8>, String> {
+        let keysyms: &[(c_ulong, &str)] = match chord {
+            PasteChord::CtrlV => &[(keysym::XK_Control_L as c_ulong, "Control_L"), (keysym::XK_v as c_ulong, "v")],
+            PasteChord::CtrlShiftV => &[
+                (keysym::XK_Control_L as c_ulong, "Control_L"),
+                (keysym::XK_Shift_L as c_ulong, "Shift_L"),
+                (keysym::XK_v as c_ulong, "v"),
+            ],
+        };
+        keysyms
+            .iter()
+            .map(|&(keysym, label)| match unsafe { (self.xlib.XKeysymToKeycode)(self.display, keysym) } {
+                0 => Err(format!("the keyboard layout on X display \"{}\" has no {label} key", self.name)),
+                keycode => Ok(keycode),
+            })
+            .collect()
+    }
+
+    fn send_key(&self, keycode: u8, down: bool) -> Result<(), String> {
+        let sent = unsafe { (self.xtest.XTestFakeKeyEvent)(self.display, keycode as u32, down as c_int, xlib::CurrentTime) };
+        if sent == 0 {
+            return Err(format!("XTestFakeKeyEvent failed for keycode {keycode}"));
+        }
+        Ok(())
+    }
+
+    fn sync(&self) {
+        unsafe {
+            (self.xlib.XSync)(self.display, xlib::False);
+        }
+    }
+
+    /// Whether the server currently has `keycode` held down.
+    #[cfg(test)]
+    fn is_pressed(&self, keycode: u8) -> bool {
+        let mut keys = [0 as std::os::raw::c_char; 32];
+        unsafe {
+            (self.xlib.XQueryKeymap)(self.display, keys.as_mut_ptr());
+        }
+        keys[keycode as usize / 8] as u8 & (1 << (keycode % 8)) != 0
+    }
+}
+
+impl Drop for Keyboard {
+    fn drop(&mut self) {
+        unsafe {
+            (self.xlib.XCloseDisplay)(self.display);
+        }
+    }
+}
+
+/// Sends the paste chord to whichever window has focus on the X display.
+pub fn paste(chord: PasteChord) -> Result<(), String> {
+    Keyboard::open()?.send_chord(chord)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Types into whatever X server `DISPLAY` names, so it only runs when
+    /// asked for, against a throwaway one:
+    /// `xvfb-run cargo test insert::x11 -- --ignored`.
+    #[test]
+    #[ignore = "sends real keystrokes; run under Xvfb with --ignored"]
+    fn chord_is_pressed_and_fully_released() {
+        let keyboard = Keyboard::open().unwrap();
+        let keys = keyboard.chord_keycodes(PasteChord::CtrlShiftV).unwrap();
+        assert_eq!(keys.len(), 3);
+        for &key in &keys {
+            keyboard.send_key(key, true).unwrap();
+        }
+        keyboard.sync();
+        assert!(keys.iter().all(|&key| keyboard.is_pressed(key)));
+
+        for &key in keys.iter().rev() {
+            keyboard.send_key(key, false).unwrap();
+        }
+        keyboard.sync();
+        assert!(keys.iter().all(|&key| !keyboard.is_pressed(key)));
+
+        keyboard.send_chord(PasteChord::CtrlV).unwrap();
+        assert!(keys.iter().all(|&key| !keyboard.is_pressed(key)));
+    }
+}
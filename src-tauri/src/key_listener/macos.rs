@@ -1,4 +1,5 @@
-use crate::{stop_recording_impl, toggle_recording_impl};
+use crate::app_state::RecordingMode;
+use crate::{stop_recording_impl, toggle_pause_impl, toggle_recording_impl};
 use core_foundation::runloop::{kCFRunLoopDefaultMode, CFRunLoop};
 use core_graphics::event::{
     CGEvent, CGEventFlags, CGEventTap, CGEventTapLocation, CGEventTapOptions,
@@ -10,6 +11,8 @@ use std::time::{Duration, Instant};
 use tauri::AppHandle;
 
 const FN_KEYCODE: i64 = 63;
+/// `P`; with Control and Option held it pauses or resumes a toggle recording.
+const PAUSE_KEYCODE: i64 = 35;
 
 #[derive(Debug)]
 struct State {
@@ -40,8 +43,11 @@ pub fn init(app: &AppHandle) -> Result<(), String> {
             CGEventTapLocation::HID,
             CGEventTapPlacement::HeadInsertEventTap,
             CGEventTapOptions::Default,
-            vec![CGEventType::FlagsChanged],
-            move |_proxy, _etype, event| handle_event(&app_cb, &shared_cb, event, hold_ms, double_click_ms),
+            vec![CGEventType::FlagsChanged, CGEventType::KeyDown],
+            move |_proxy, etype, event| match etype {
+                CGEventType::KeyDown => handle_key_down(&app_cb, event),
+                _ => handle_event(&app_cb, &shared_cb, event, hold_ms, double_click_ms),
+            },
         );
 
         let Ok(tap) = tap else {
@@ -66,6 +72,24 @@ pub fn init(app: &AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+fn handle_key_down(app: &AppHandle, event: &CGEvent) -> Option<CGEvent> {
+    let keycode = event.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE);
+    let flags = event.get_flags();
+    let repeat = event.get_integer_value_field(EventField::KEYBOARD_EVENT_AUTOREPEAT) != 0;
+    if keycode != PAUSE_KEYCODE || !flags.contains(CGEventFlags::CGEventFlagControl | CGEventFlags::CGEventFlagAlternate) {
+        return Some(event.clone());
+    }
+    // The hotkey is swallowed, repeats included, so it never reaches the app
+    // being dictated into.
+    if !repeat {
+        let app2 = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let _ = toggle_pause_impl(&app2);
+        });
+    }
+    None
+}
+
 fn handle_event(
     app: &AppHandle,
     shared: &Arc<Mutex<State>>,
@@ -102,7 +126,7 @@ fn handle_event(
                 st.hold_fired = true;
                 drop(st);
                 tauri::async_runtime::spawn(async move {
-                    let _ = toggle_recording_impl(app2).await;
+                    let _ = toggle_recording_impl(app2, RecordingMode::Hold).await;
                 });
             }
         });
@@ -139,7 +163,7 @@ fn handle_event(
                 st.last_tap_at = None;
                 let app2 = app.clone();
                 tauri::async_runtime::spawn(async move {
-                    let _ = toggle_recording_impl(app2).await;
+                    let _ = toggle_recording_impl(app2, RecordingMode::Toggle).await;
                 });
                 return None;
             }
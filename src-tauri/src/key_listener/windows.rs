@@ -1,4 +1,5 @@
-use crate::{stop_recording_impl, toggle_recording_impl};
+use crate::app_state::RecordingMode;
+use crate::{stop_recording_impl, toggle_pause_impl, toggle_recording_impl};
 use std::sync::{Mutex, OnceLock};
 use std::time::{Duration, Instant};
 use tauri::AppHandle;
@@ -30,6 +31,10 @@ struct State {
     pressed_ctrl: bool,
     pressed_alt: bool,
     pressed_key: bool,
+    pressed_pause_key: bool,
+    /// The pause key went down as the pause hotkey; its repeats and release
+    /// are kept from the focused app too.
+    pause_key_swallowed: bool,
     key_down_at: Option<Instant>,
     hold_fired: bool,
     last_tap_at: Option<Instant>,
@@ -37,6 +42,7 @@ struct State {
 
 static APP: OnceLock<AppHandle> = OnceLock::new();
 static HOTKEY: OnceLock<Mutex<Hotkey>> = OnceLock::new();
+static PAUSE_HOTKEY: OnceLock<Mutex<Option<Hotkey>>> = OnceLock::new();
 static THRESHOLDS: OnceLock<Mutex<Thresholds>> = OnceLock::new();
 static STATE: OnceLock<Mutex<State>> = OnceLock::new();
 static HOOK: OnceLock<Mutex<Option<HHOOK>>> = OnceLock::new();
@@ -58,6 +64,10 @@ pub fn init(app: &AppHandle) -> Result<(), String> {
     APP.set(app.clone()).ok();
     let cfg = crate::config::load_or_default(app)?;
     HOTKEY.get_or_init(|| Mutex::new(parse_hotkey(&cfg.hotkey.windows)));
+    PAUSE_HOTKEY.get_or_init(|| {
+        let pause = cfg.hotkey.pause_windows.trim();
+        Mutex::new((!pause.is_empty()).then(|| parse_hotkey(pause)))
+    });
     THRESHOLDS.get_or_init(|| {
         Mutex::new(Thresholds {
             hold_ms: cfg.thresholds.hold_ms,
@@ -71,6 +81,8 @@ pub fn init(app: &AppHandle) -> Result<(), String> {
             pressed_ctrl: false,
             pressed_alt: false,
             pressed_key: false,
+            pressed_pause_key: false,
+            pause_key_swallowed: false,
             key_down_at: None,
             hold_fired: false,
             last_tap_at: None,
@@ -121,6 +133,7 @@ unsafe extern "system" fn hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -
         .and_then(|m| m.lock().ok())
         .map(|g| *g)
         .unwrap_or(parse_hotkey("RightCtrl"));
+    let pause_hotkey = PAUSE_HOTKEY.get().and_then(|m| m.lock().ok()).and_then(|g| *g);
     let thresholds = THRESHOLDS
         .get()
         .and_then(|m| m.lock().ok())
@@ -142,6 +155,31 @@ unsafe extern "system" fn hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -
         _ => {}
     }
 
+    if let Some(pause_hotkey) = pause_hotkey.filter(|p| key_matches(p.key_vk, vk)) {
+        // Fires once per press; auto-repeat does not toggle it back.
+        if is_down && !st.pressed_pause_key {
+            st.pressed_pause_key = true;
+            st.pause_key_swallowed = modifiers_match(&pause_hotkey, &st);
+            if st.pause_key_swallowed {
+                if let Some(app) = APP.get() {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let _ = toggle_pause_impl(&app);
+                    });
+                }
+            }
+        } else if !is_down {
+            st.pressed_pause_key = false;
+        }
+        // Forwarding the hotkey would type a character on AltGr layouts.
+        if st.pause_key_swallowed {
+            if !is_down {
+                st.pause_key_swallowed = false;
+            }
+            return LRESULT(1);
+        }
+    }
+
     if key_matches(hotkey.key_vk, vk) {
         if is_down && !st.pressed_key {
             st.pressed_key = true;
@@ -181,7 +219,7 @@ unsafe extern "system" fn hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -
                         if let Some(app) = APP.get() {
                             let app = app.clone();
                             tauri::async_runtime::spawn(async move {
-                                let _ = toggle_recording_impl(app).await;
+                                let _ = toggle_recording_impl(app, RecordingMode::Toggle).await;
                             });
                         }
                     }
@@ -223,7 +261,7 @@ fn spawn_hold_timer(hold_ms: u64, pressed_at: Instant) {
             if let Some(app) = APP.get() {
                 let app = app.clone();
                 tauri::async_runtime::spawn(async move {
-                    let _ = toggle_recording_impl(app).await;
+                    let _ = toggle_recording_impl(app, RecordingMode::Hold).await;
                 });
             }
         }
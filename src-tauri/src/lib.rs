@@ -104,9 +104,15 @@ fn get_config(app: tauri::AppHandle) -> Result<config::Config, String> {
 
 #[tauri::command]
 fn set_config(app: tauri::AppHandle, config: config::Config) -> Result<(), String> {
+    let previous = config::load_or_default(&app).ok();
     config::save(&app, &config).inspect_err(|e| {
         let _ = logger::append_error(&app, "set_config", e);
-    })
+    })?;
+    // Saving unrelated settings leaves the open microphone alone.
+    if previous.is_none_or(|previous| previous.recording.warm_input_differs(&config.recording)) {
+        spawn_refresh_warm_input(app, true);
+    }
+    Ok(())
 }
 
 #[tauri::command]
@@ -120,9 +126,93 @@ fn reset_config(app: tauri::AppHandle) -> Result<config::Config, String> {
     config::save(&app, &next).inspect_err(|e| {
         let _ = logger::append_error(&app, "reset_config", e);
     })?;
+    if current.recording.warm_input_differs(&next.recording) {
+        spawn_refresh_warm_input(app, true);
+    }
     Ok(next)
 }
 
+/// Opens or closes the pre-roll input to match `recording.preRoll`. With
+/// `reopen`, an open input is replaced so config changes take effect; without
+/// it, only a released input is reopened. Left alone while recording.
+fn refresh_warm_input(app: &tauri::AppHandle, reopen: bool) {
+    let cfg = match config::load_or_default(app) {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            let _ = logger::append_error(app, "pre_roll", &e);
+            return;
+        }
+    };
+    // Synthetic sources have no microphone to keep open.
+    let enabled = cfg.recording.pre_roll.enabled
+        && audio::effective_source(&cfg.recording.source)
+            .is_ok_and(|source| source.kind == config::SourceKind::Device);
+
+    let state = app.state::<Mutex<app_state::RuntimeState>>();
+    let token = {
+        let Ok(mut s) = state.lock() else {
+            return;
+        };
+        if s.status.is_recording() {
+            return;
+        }
+        let is_open = s.warm_input.as_ref().is_some_and(|w| w.is_open());
+        if enabled && is_open && !reopen {
+            return;
+        }
+        if let Some(old) = s.warm_input.take() {
+            old.close();
+        }
+        s.warm_input_token = s.warm_input_token.wrapping_add(1);
+        s.warm_input_token
+    };
+
+    let opened = if enabled {
+        let app2 = app.clone();
+        audio::WarmInput::open(&cfg.recording, move || on_warm_input_closed(&app2, token))
+            .inspect_err(|e| {
+                let _ = logger::append_error(app, "pre_roll", e);
+            })
+            .ok()
+    } else {
+        None
+    };
+
+    let Ok(mut s) = state.lock() else {
+        return;
+    };
+    if s.warm_input_token != token {
+        if let Some(warm) = opened {
+            warm.close();
+        }
+        return;
+    }
+    s.warm_input = opened;
+    s.status.mic_open = s.warm_input.is_some();
+    let status = s.status.clone();
+    drop(s);
+    emit_status(app, &status);
+}
+
+fn spawn_refresh_warm_input(app: tauri::AppHandle, reopen: bool) {
+    tauri::async_runtime::spawn_blocking(move || refresh_warm_input(&app, reopen));
+}
+
+fn on_warm_input_closed(app: &tauri::AppHandle, token: u64) {
+    let state = app.state::<Mutex<app_state::RuntimeState>>();
+    let Ok(mut s) = state.lock() else {
+        return;
+    };
+    if s.warm_input_token != token {
+        return;
+    }
+    s.warm_input = None;
+    s.status.mic_open = false;
+    let status = s.status.clone();
+    drop(s);
+    emit_status(app, &status);
+}
+
 #[tauri::command]
 fn get_autostart_enabled(app: tauri::AppHandle) -> Result<bool, String> {
     app.autolaunch()
@@ -152,25 +242,37 @@ fn set_autostart_enabled(app: tauri::AppHandle, enabled: bool) -> Result<(), Str
     }
 }
 
+#[tauri::command]
+async fn list_input_devices(app: tauri::AppHandle) -> Result<Vec<audio::InputDeviceInfo>, String> {
+    tauri::async_runtime::spawn_blocking(audio::list_input_devices)
+        .await
+        .map_err(|e| format!("device listing task failed: {e}"))?
+        .inspect_err(|e| {
+            let _ = logger::append_error(&app, "list_input_devices", e);
+        })
+}
+
 #[tauri::command]
 async fn test_transcription(app: tauri::AppHandle) -> Result<String, String> {
     let cfg = config::load_or_default(&app).inspect_err(|e| {
         let _ = logger::append_error(&app, "test_transcription", e);
     })?;
 
-    let wav_path = tauri::async_runtime::spawn_blocking(move || {
-        let tmp = std::env::temp_dir().join(format!(
-            "voicedictation-test-{}.wav",
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map_err(|e| format!("time error: {e}"))?
-                .as_millis()
-        ));
-
-        let handle = audio::start_recording(tmp.clone())?;
+    let recording_cfg = cfg.recording.clone();
+    let recorded = tauri::async_runtime::spawn_blocking(move || {
+        let target = recording_target(&recording_cfg, "voicedictation-test")?;
+        let (events, _) = crossbeam_channel::unbounded();
+        let options = audio::RecordingOptions {
+            config: recording_cfg,
+            events,
+            raw_copy_path: None,
+            channels_copy_path: None,
+            warm_input: None,
+            segments: None,
+        };
+        let handle = audio::start_recording(target, options)?;
         std::thread::sleep(std::time::Duration::from_millis(3000));
-        let path = handle.stop()?;
-        Ok::<_, String>(path)
+        handle.stop(Duration::ZERO).map(|finished| finished.audio)
     })
     .await
     .map_err(|e| format!("recording task failed: {e}"))
@@ -178,61 +280,301 @@ async fn test_transcription(app: tauri::AppHandle) -> Result<String, String> {
         let _ = logger::append_error(&app, "test_transcription", e);
     })??;
 
-    let text = azure_transcribe::transcribe_wav(&wav_path, &cfg)
+    let text = azure_transcribe::transcribe_wav(&recorded, &cfg).await;
+    recorded.discard();
+    text.inspect_err(|e| {
+        let _ = logger::append_error(&app, "test_transcription", e);
+    })
+}
+
+#[tauri::command]
+async fn list_output_devices(app: tauri::AppHandle) -> Result<Vec<audio::OutputDeviceInfo>, String> {
+    tauri::async_runtime::spawn_blocking(audio::list_output_devices)
         .await
+        .map_err(|e| format!("device listing task failed: {e}"))?
         .inspect_err(|e| {
-            let _ = logger::append_error(&app, "test_transcription", e);
+            let _ = logger::append_error(&app, "list_output_devices", e);
+        })
+}
+
+#[tauri::command]
+async fn list_loopback_devices(app: tauri::AppHandle) -> Result<Vec<audio::LoopbackDeviceInfo>, String> {
+    tauri::async_runtime::spawn_blocking(audio::list_loopback_devices)
+        .await
+        .map_err(|e| format!("device listing task failed: {e}"))?
+        .inspect_err(|e| {
+            let _ = logger::append_error(&app, "list_loopback_devices", e);
+        })
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MicrophoneTest {
+    output_device: String,
+    stats: audio::InputStats,
+    warnings: Vec<String>,
+}
+
+/// Records a short clip through the normal capture path and plays it back on
+/// `output_device` (the default output when unset). Needs no network access.
+#[tauri::command]
+async fn test_microphone(app: tauri::AppHandle, output_device: Option<String>) -> Result<MicrophoneTest, String> {
+    let cfg = config::load_or_default(&app).inspect_err(|e| {
+        let _ = logger::append_error(&app, "test_microphone", e);
+    })?;
+
+    let recording_cfg = cfg.recording.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let quality = recording_cfg.resample_quality;
+        let (events, _) = crossbeam_channel::unbounded();
+        let options = audio::RecordingOptions {
+            config: recording_cfg,
+            events,
+            raw_copy_path: None,
+            channels_copy_path: None,
+            warm_input: None,
+            segments: None,
+        };
+        let handle = audio::start_recording(audio::RecordingTarget::Memory, options)?;
+        std::thread::sleep(std::time::Duration::from_millis(3000));
+        let finished = handle.stop(Duration::ZERO)?;
+        let output_device = audio::play_recording(&finished.audio, output_device.as_deref(), quality)?;
+        Ok::<_, String>(MicrophoneTest {
+            output_device,
+            warnings: finished.stats.warnings(),
+            stats: finished.stats,
+        })
+    })
+    .await
+    .map_err(|e| format!("microphone test task failed: {e}"))?
+    .inspect_err(|e| {
+        let _ = logger::append_error(&app, "test_microphone", e);
+    })
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DenoiseComparison {
+    raw_path: String,
+    denoised_path: String,
+}
+
+/// Folder for `record_denoise_comparison` clips. It is emptied before each
+/// new comparison and at startup, so voice recordings do not pile up.
+fn denoise_comparison_dir() -> std::path::PathBuf {
+    std::env::temp_dir().join("voicedictation-denoise")
+}
+
+/// Folder for the two-channel copies `recording.meeting.keepSeparateChannels`
+/// keeps. It is emptied before each new recording and at startup.
+fn meeting_channels_dir() -> std::path::PathBuf {
+    std::env::temp_dir().join("voicedictation-meeting")
+}
+
+/// Removes `dir` and everything in it; a missing folder is not an error.
+fn clear_temp_dir(dir: &std::path::Path) -> Result<(), String> {
+    match std::fs::remove_dir_all(dir) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("failed to remove {}: {e}", dir.display()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Records a short clip with noise suppression on and keeps an unprocessed copy
+/// next to it, so the two can be compared by ear. The rest of the input
+/// processing is off, so the only difference between them is the denoiser.
+#[tauri::command]
+async fn record_denoise_comparison(app: tauri::AppHandle) -> Result<DenoiseComparison, String> {
+    let cfg = config::load_or_default(&app).inspect_err(|e| {
+        let _ = logger::append_error(&app, "record_denoise_comparison", e);
+    })?;
+
+    let mut recording_cfg = cfg.recording.clone();
+    recording_cfg.denoise = true;
+    recording_cfg.trim.enabled = false;
+    recording_cfg.vad.auto_stop = false;
+    recording_cfg.dsp.high_pass = false;
+    recording_cfg.dsp.normalize = false;
+    recording_cfg.dsp.limiter = false;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let dir = denoise_comparison_dir();
+        clear_temp_dir(&dir)?;
+        std::fs::create_dir_all(&dir).map_err(|e| format!("failed to create {}: {e}", dir.display()))?;
+        let ts = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(|e| format!("time error: {e}"))?
+            .as_millis();
+        let raw = dir.join(format!("denoise-{ts}-raw.wav"));
+        let denoised = dir.join(format!("denoise-{ts}-denoised.wav"));
+
+        let (events, _) = crossbeam_channel::unbounded();
+        let options = audio::RecordingOptions {
+            config: recording_cfg,
+            events,
+            raw_copy_path: Some(raw.clone()),
+            channels_copy_path: None,
+            warm_input: None,
+            segments: None,
+        };
+        let handle = audio::start_recording(audio::RecordingTarget::File(denoised.clone()), options)?;
+        std::thread::sleep(std::time::Duration::from_millis(5000));
+        handle.stop(Duration::ZERO)?;
+        Ok::<_, String>(DenoiseComparison {
+            raw_path: raw.display().to_string(),
+            denoised_path: denoised.display().to_string(),
+        })
+    })
+    .await
+    .map_err(|e| format!("recording task failed: {e}"))?
+    .inspect_err(|e| {
+        let _ = logger::append_error(&app, "record_denoise_comparison", e);
+    })
+}
+
+/// Transcribes an existing audio file with the current recording settings.
+/// The text is returned and, if asked, left on the clipboard; nothing is pasted.
+#[tauri::command]
+async fn transcribe_file(app: tauri::AppHandle, path: String, copy_to_clipboard: bool) -> Result<String, String> {
+    let cfg = config::load_or_default(&app).inspect_err(|e| {
+        let _ = logger::append_error(&app, "transcribe_file", e);
+    })?;
+
+    let recording_cfg = cfg.recording.clone();
+    let decoded = tauri::async_runtime::spawn_blocking(move || {
+        let path = std::path::Path::new(&path);
+        // Refuse before decoding what could not be uploaded anyway.
+        let limit = azure_transcribe::max_upload_seconds();
+        if let Some(seconds) = audio::file_seconds(path)?.filter(|&seconds| seconds > limit) {
+            return Err(format!(
+                "{} lasts {:.1} minutes; at most {:.1} minutes can be transcribed at once",
+                path.display(),
+                seconds / 60.0,
+                limit / 60.0
+            ));
+        }
+        audio::decode_file(path, &recording_cfg)
+    })
+    .await
+    .map_err(|e| format!("decode task failed: {e}"))
+    .inspect_err(|e| {
+        let _ = logger::append_error(&app, "transcribe_file", e);
+    })??;
+
+    let text = azure_transcribe::transcribe_wav(&decoded.audio, &cfg)
+        .await
+        .inspect_err(|e| {
+            let _ = logger::append_error(&app, "transcribe_file", e);
+        })?;
+    if copy_to_clipboard && !text.is_empty() {
+        insert::copy_text(&text).inspect_err(|e| {
+            let _ = logger::append_error(&app, "transcribe_file", e);
         })?;
-    let _ = std::fs::remove_file(&wav_path);
+    }
     Ok(text)
 }
 
+/// `capture` picks microphone, system audio or both (a meeting) for this
+/// recording; unset uses `recording.capture`.
 #[tauri::command]
 async fn toggle_recording(
     app: tauri::AppHandle,
+    capture: Option<config::CaptureMode>,
 ) -> Result<(), String> {
-    toggle_recording_impl(app.clone())
+    toggle_recording_with(app.clone(), app_state::RecordingMode::Toggle, capture)
         .await
         .inspect_err(|e| {
             let _ = logger::append_error(&app, "toggle_recording", e);
         })
 }
 
-pub(crate) async fn toggle_recording_impl(app: tauri::AppHandle) -> Result<(), String> {
+pub(crate) async fn toggle_recording_impl(
+    app: tauri::AppHandle,
+    mode: app_state::RecordingMode,
+) -> Result<(), String> {
+    toggle_recording_with(app, mode, None).await
+}
+
+async fn toggle_recording_with(
+    app: tauri::AppHandle,
+    mode: app_state::RecordingMode,
+    capture: Option<config::CaptureMode>,
+) -> Result<(), String> {
     let state = app.state::<Mutex<app_state::RuntimeState>>();
     let should_stop = {
         let s = state.lock().map_err(|_| "state mutex poisoned".to_string())?;
         if s.status.state == "Transcribing" || s.status.state == "Inserting" {
             return Err("Busy".to_string());
         }
-        s.status.state == "Recording"
+        s.status.is_recording()
     };
 
     if should_stop {
         return stop_recording_impl(app).await;
     }
 
-    let cfg = config::load_or_default(&app).inspect_err(|e| {
+    let mut cfg = config::load_or_default(&app).inspect_err(|e| {
         let _ = logger::append_error(&app, "toggle_recording", e);
     })?;
+    if let Some(capture) = capture {
+        cfg.recording.capture = capture;
+    }
+    // Push-to-talk recordings are short; their text goes in on release.
+    if mode != app_state::RecordingMode::Toggle {
+        cfg.recording.incremental.enabled = false;
+    }
     let max_seconds = cfg.recording.max_seconds.max(1);
 
-    let tmp = std::env::temp_dir().join(format!(
-        "voicedictation-{}.wav",
-        std::time::SystemTime::now()
+    let target = recording_target(&cfg.recording, "voicedictation")?;
+    let warm_input = state
+        .lock()
+        .map_err(|_| "state mutex poisoned".to_string())?
+        .warm_input
+        .clone();
+    let keep_channels =
+        cfg.recording.capture == config::CaptureMode::Meeting && cfg.recording.meeting.keep_separate_channels;
+    if keep_channels && cfg.recording.in_memory {
+        return Err("recording.meeting.keepSeparateChannels writes a WAV file, so it cannot be used with recording.inMemory".to_string());
+    }
+    // The previous recording's copy goes once a new recording starts.
+    let channels_dir = meeting_channels_dir();
+    if let Err(e) = clear_temp_dir(&channels_dir) {
+        let _ = logger::append_error(&app, "toggle_recording", &e);
+    }
+    let channels_copy_path = if keep_channels {
+        std::fs::create_dir_all(&channels_dir)
+            .map_err(|e| format!("failed to create {}: {e}", channels_dir.display()))?;
+        let ts = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .map_err(|e| format!("time error: {e}"))?
-            .as_millis()
-    ));
-
-    let handle = audio::start_recording(tmp.clone())?;
+            .as_millis();
+        Some(channels_dir.join(format!("meeting-{ts}-channels.wav")))
+    } else {
+        None
+    };
+    let (events_tx, events_rx) = crossbeam_channel::unbounded();
+    let (segments_tx, segments_rx) = crossbeam_channel::unbounded();
+    let segmented = cfg.recording.rollover.enabled || cfg.recording.incremental.enabled;
+    let options = audio::RecordingOptions {
+        config: cfg.recording.clone(),
+        events: events_tx,
+        raw_copy_path: None,
+        channels_copy_path,
+        warm_input,
+        segments: segmented.then_some(segments_tx),
+    };
+    let handle = audio::start_recording(target, options)?;
     let mut s = state.lock().map_err(|_| "state mutex poisoned".to_string())?;
     s.recording = Some(handle);
-    s.recording_path = Some(tmp);
+    s.recording_mode = mode;
+    s.active_time = app_state::ActiveTime::started(std::time::Instant::now());
     s.status.state = "Recording".to_string();
     s.status.last_error = None;
     s.recording_token = s.recording_token.wrapping_add(1);
     let token = s.recording_token;
+    // Set with the recording, so a stop that follows right away waits for its segments.
+    s.segment_worker = segmented.then(|| spawn_segment_worker(app.clone(), cfg.clone(), token, segments_rx));
 
     let status = s.status.clone();
     drop(s);
@@ -241,22 +583,289 @@ pub(crate) async fn toggle_recording_impl(app: tauri::AppHandle) -> Result<(), S
         play_start_sound();
     }
 
+    spawn_recording_event_listener(app.clone(), token, events_rx);
+
+    if !cfg.recording.rollover.enabled {
+        let app2 = app.clone();
+        let limit = Duration::from_secs(max_seconds);
+        let warning = Duration::from_secs(cfg.recording.limit_warning_seconds);
+        tauri::async_runtime::spawn_blocking(move || run_time_limit(app2, token, limit, warning));
+    }
+
+    Ok(())
+}
+
+/// Transcribes and inserts the segments a recording hands off (rollover or
+/// incremental insertion) while capture continues. Segments are transcribed
+/// concurrently, but their text goes in strictly in recording order. The
+/// worker finishes with whether any text went in.
+fn spawn_segment_worker(
+    app: tauri::AppHandle,
+    cfg: config::Config,
+    token: u64,
+    segments: crossbeam_channel::Receiver<audio::FinishedRecording>,
+) -> tauri::async_runtime::JoinHandle<bool> {
+    let (transcripts_tx, transcripts_rx) = crossbeam_channel::unbounded();
     let app2 = app.clone();
+    let cfg2 = cfg.clone();
     tauri::async_runtime::spawn_blocking(move || {
-        std::thread::sleep(Duration::from_secs(max_seconds));
-        let state = app2.state::<Mutex<app_state::RuntimeState>>();
-        let should_stop = state
-            .lock()
-            .ok()
-            .is_some_and(|s| s.status.state == "Recording" && s.recording_token == token);
-        if should_stop {
-            let _ = tauri::async_runtime::block_on(stop_recording_impl(app2));
+        for segment in segments.iter() {
+            log_input_stats(&app2, &segment.stats, false);
+            let cfg = cfg2.clone();
+            let transcript = tauri::async_runtime::spawn(async move {
+                let text = azure_transcribe::transcribe_wav(&segment.audio, &cfg).await;
+                segment.audio.discard();
+                text
+            });
+            let _ = transcripts_tx.send(transcript);
         }
     });
 
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut inserted = false;
+        for transcript in transcripts_rx.iter() {
+            let result = tauri::async_runtime::block_on(transcript)
+                .map_err(|e| format!("transcription task failed: {e}"))
+                .and_then(|text| text)
+                .and_then(|text| insert_segment_text(&app, &text, inserted, &cfg));
+            match result {
+                Ok(went_in) => inserted |= went_in,
+                Err(e) => show_recording_warning(&app, token, &format!("segment not inserted: {e}")),
+            }
+        }
+        inserted
+    })
+}
+
+/// Inserts one segment's text, after a space when text was inserted before it.
+/// Returns whether anything was inserted.
+fn insert_segment_text(app: &tauri::AppHandle, text: &str, follows_text: bool, cfg: &config::Config) -> Result<bool, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(false);
+    }
+    let text = if follows_text { format!(" {text}") } else { text.to_string() };
+    insert::insert_text(&text, &cfg.insert)?;
+    let _ = app.emit("transcript_ready", text);
+    Ok(true)
+}
+
+/// Sends `recording_limit_warning` with the seconds left before the
+/// recording is cut off.
+fn warn_of_limit(app: &tauri::AppHandle, seconds_left: u64) {
+    let _ = app.emit("recording_limit_warning", seconds_left);
+}
+
+/// How often the time limit is rechecked while a recording is paused.
+const PAUSED_TIME_LIMIT_POLL: Duration = Duration::from_millis(250);
+
+/// Ends the recording identified by `token` once it has captured for `limit`,
+/// warning `warning` before; time spent paused does not count.
+fn run_time_limit(app: tauri::AppHandle, token: u64, limit: Duration, warning: Duration) {
+    let mut warned = warning.is_zero();
+    loop {
+        let remaining = {
+            let state = app.state::<Mutex<app_state::RuntimeState>>();
+            let Ok(s) = state.lock() else {
+                return;
+            };
+            if !s.status.is_recording() || s.recording_token != token {
+                return;
+            }
+            if s.active_time.is_paused() {
+                None
+            } else {
+                Some(limit.saturating_sub(s.active_time.elapsed(std::time::Instant::now())))
+            }
+        };
+        let Some(remaining) = remaining else {
+            std::thread::sleep(PAUSED_TIME_LIMIT_POLL);
+            continue;
+        };
+        if remaining.is_zero() {
+            stop_current_recording(&app, token);
+            return;
+        }
+        if !warned && remaining <= warning {
+            warned = true;
+            warn_of_limit(&app, remaining.as_secs_f64().ceil() as u64);
+        }
+        std::thread::sleep(if warned { remaining } else { remaining - warning });
+    }
+}
+
+#[tauri::command]
+fn pause_recording(app: tauri::AppHandle) -> Result<(), String> {
+    set_recording_paused(&app, true).inspect_err(|e| {
+        let _ = logger::append_error(&app, "pause_recording", e);
+    })
+}
+
+#[tauri::command]
+fn resume_recording(app: tauri::AppHandle) -> Result<(), String> {
+    set_recording_paused(&app, false).inspect_err(|e| {
+        let _ = logger::append_error(&app, "resume_recording", e);
+    })
+}
+
+/// Pauses the running recording, or resumes it if it is already paused.
+pub(crate) fn toggle_pause_impl(app: &tauri::AppHandle) -> Result<(), String> {
+    let paused = {
+        let state = app.state::<Mutex<app_state::RuntimeState>>();
+        let s = state.lock().map_err(|_| "state mutex poisoned".to_string())?;
+        s.status.state == "Paused"
+    };
+    set_recording_paused(app, !paused)
+}
+
+/// Suspends or resumes capture of a toggle-mode recording. Audio in between
+/// is discarded and the pause does not count toward `recording.maxSeconds`.
+fn set_recording_paused(app: &tauri::AppHandle, paused: bool) -> Result<(), String> {
+    let state = app.state::<Mutex<app_state::RuntimeState>>();
+    let mut s = state.lock().map_err(|_| "state mutex poisoned".to_string())?;
+    let (from, to) = if paused { ("Recording", "Paused") } else { ("Paused", "Recording") };
+    if s.status.state != from {
+        return Err(if paused { "Not recording" } else { "Not paused" }.to_string());
+    }
+    if s.recording_mode != app_state::RecordingMode::Toggle {
+        return Err("Only toggle-mode recordings can be paused".to_string());
+    }
+    let now = std::time::Instant::now();
+    if paused {
+        s.active_time.pause(now);
+    } else {
+        s.active_time.resume(now);
+    }
+    if let Some(handle) = s.recording.as_ref() {
+        handle.set_paused(paused);
+    }
+    s.status.state = to.to_string();
+    let status = s.status.clone();
+    drop(s);
+    emit_status(app, &status);
     Ok(())
 }
 
+/// Logs a problem with the running recording and shows it in the status
+/// without ending the recording.
+fn show_recording_warning(app: &tauri::AppHandle, token: u64, message: &str) {
+    let _ = logger::append_error(app, "recording", message);
+    let state = app.state::<Mutex<app_state::RuntimeState>>();
+    let status = state.lock().ok().and_then(|mut s| {
+        if !s.status.is_recording() || s.recording_token != token {
+            return None;
+        }
+        s.status.last_error = Some(message.to_string());
+        Some(s.status.clone())
+    });
+    if let Some(status) = status {
+        emit_status(app, &status);
+    }
+}
+
+/// Stops and transcribes the recording identified by `token`, if it is still running.
+fn stop_current_recording(app: &tauri::AppHandle, token: u64) {
+    let state = app.state::<Mutex<app_state::RuntimeState>>();
+    let should_stop = state
+        .lock()
+        .ok()
+        .is_some_and(|s| s.status.is_recording() && s.recording_token == token);
+    if should_stop {
+        let _ = tauri::async_runtime::block_on(end_recording(app.clone(), false));
+    }
+}
+
+/// Temporary WAV file named `<prefix>-<ms>.wav`, or memory when `inMemory` is set.
+fn recording_target(cfg: &config::RecordingConfig, prefix: &str) -> Result<audio::RecordingTarget, String> {
+    if cfg.in_memory {
+        return Ok(audio::RecordingTarget::Memory);
+    }
+    Ok(audio::RecordingTarget::File(std::env::temp_dir().join(format!(
+        "{prefix}-{}.wav",
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(|e| format!("time error: {e}"))?
+            .as_millis()
+    ))))
+}
+
+fn spawn_recording_event_listener(
+    app: tauri::AppHandle,
+    token: u64,
+    events: crossbeam_channel::Receiver<audio::RecordingEvent>,
+) {
+    tauri::async_runtime::spawn_blocking(move || {
+        for event in events.iter() {
+            match event {
+                audio::RecordingEvent::SilenceDetected => {
+                    let state = app.state::<Mutex<app_state::RuntimeState>>();
+                    let should_stop = state.lock().ok().is_some_and(|s| {
+                        s.status.state == "Recording"
+                            && s.recording_token == token
+                            && s.recording_mode == app_state::RecordingMode::Toggle
+                    });
+                    if should_stop {
+                        let _ = tauri::async_runtime::block_on(end_recording(app.clone(), false));
+                    }
+                }
+                audio::RecordingEvent::InputDeviceFallback { requested, using } => {
+                    let _ = logger::append_error(
+                        &app,
+                        "recording",
+                        &format!("input device \"{requested}\" not found; using \"{using}\""),
+                    );
+                }
+                audio::RecordingEvent::StreamError { message } => {
+                    show_recording_warning(&app, token, &message);
+                }
+                audio::RecordingEvent::InputDeviceSwitched { from, to } => {
+                    let message = format!("input device \"{from}\" was lost; recording continues on \"{to}\"");
+                    show_recording_warning(&app, token, &message);
+                }
+                audio::RecordingEvent::InputDeviceLost => stop_current_recording(&app, token),
+                audio::RecordingEvent::Level(level) => {
+                    let _ = app.emit("audio_level", level);
+                }
+                audio::RecordingEvent::LimitApproaching { seconds_left } => warn_of_limit(&app, seconds_left),
+                audio::RecordingEvent::Failed { stats } => report_input_stats(&app, &stats, true),
+                audio::RecordingEvent::MemoryCapReached => {
+                    let _ = logger::append_error(&app, "recording", "in-memory recording reached its memory cap");
+                    stop_current_recording(&app, token);
+                }
+            }
+        }
+    });
+}
+
+/// Logs input statistics when they point at a problem, that is when the
+/// input drew warnings or the recording `failed`, and returns the warnings.
+fn log_input_stats(app: &tauri::AppHandle, stats: &audio::InputStats, failed: bool) -> Vec<String> {
+    let warnings = stats.warnings();
+    if failed || !warnings.is_empty() {
+        let _ = logger::append_error(app, "input_stats", &stats.to_string());
+    }
+    for warning in &warnings {
+        let _ = logger::append_error(app, "input_stats", warning);
+    }
+    warnings
+}
+
+/// Logs a recording's input statistics (see `log_input_stats`) and publishes
+/// them, with any input warnings, in the status.
+fn report_input_stats(app: &tauri::AppHandle, stats: &audio::InputStats, failed: bool) {
+    let warnings = log_input_stats(app, stats, failed);
+
+    let state = app.state::<Mutex<app_state::RuntimeState>>();
+    let Ok(mut s) = state.lock() else {
+        return;
+    };
+    s.status.input_stats = Some(*stats);
+    s.status.input_warning = (!warnings.is_empty()).then(|| warnings.join("; "));
+    let status = s.status.clone();
+    drop(s);
+    emit_status(app, &status);
+}
+
 #[tauri::command]
 async fn stop_recording(
     app: tauri::AppHandle,
@@ -264,19 +873,28 @@ async fn stop_recording(
     stop_recording_impl(app).await
 }
 
+/// Stops the recording on user request, keeping `recording.tail` of capture
+/// after the request so the last word is not cut off.
 pub(crate) async fn stop_recording_impl(app: tauri::AppHandle) -> Result<(), String> {
+    end_recording(app, true).await
+}
+
+/// Stops, transcribes and inserts the current recording. Automatic stops (time
+/// limit, silence, lost device) pass `user_requested = false` and skip the tail.
+async fn end_recording(app: tauri::AppHandle, user_requested: bool) -> Result<(), String> {
     let cfg = config::load_or_default(&app).inspect_err(|e| {
         let _ = logger::append_error(&app, "stop_recording", e);
     })?;
 
     let state = app.state::<Mutex<app_state::RuntimeState>>();
-    let (handle, _wav_path, transcribing_status) = {
+    let (handle, transcribing_status, was_paused) = {
         let mut s = state.lock().map_err(|_| "state mutex poisoned".to_string())?;
-        if s.status.state != "Recording" {
+        if !s.status.is_recording() {
             let e = "Not recording".to_string();
             let _ = logger::append_error(&app, "stop_recording", &e);
             return Err(e);
         }
+        let was_paused = s.status.state == "Paused";
         s.status.state = "Transcribing".to_string();
         let status = s.status.clone();
 
@@ -284,21 +902,35 @@ pub(crate) async fn stop_recording_impl(app: tauri::AppHandle) -> Result<(), Str
             .recording
             .take()
             .ok_or_else(|| "recording handle missing".to_string())?;
-        let wav_path = s
-            .recording_path
-            .take()
-            .ok_or_else(|| "recording path missing".to_string())?;
-        (handle, wav_path, status)
+        (handle, status, was_paused)
     };
     emit_status(&app, &transcribing_status);
-    if cfg.sound.enabled {
-        play_stop_sound();
-    }
 
-    let stop_result = tauri::async_runtime::spawn_blocking(move || handle.stop())
+    // A paused recording has nothing to finish saying.
+    let tail = if user_requested && !was_paused {
+        Duration::from_millis(cfg.recording.tail.duration_ms)
+    } else {
+        Duration::ZERO
+    };
+    let stop_result = tauri::async_runtime::spawn_blocking(move || handle.stop(tail))
         .await
         .map_err(|e| format!("recording stop task failed: {e}"));
-    let wav_path = match stop_result {
+    spawn_refresh_warm_input(app.clone(), false);
+    // Played once capture has ended so the tail does not pick it up.
+    if cfg.sound.enabled {
+        play_stop_sound();
+    }
+    // Earlier segments go in before this one.
+    let segment_worker = state
+        .lock()
+        .map_err(|_| "state mutex poisoned".to_string())?
+        .segment_worker
+        .take();
+    let follows_segments = match segment_worker {
+        Some(worker) => worker.await.unwrap_or(false),
+        None => false,
+    };
+    let recorded = match stop_result {
         Ok(result) => result,
         Err(e) => {
             let mut s = state.lock().map_err(|_| "state mutex poisoned".to_string())?;
@@ -312,8 +944,11 @@ pub(crate) async fn stop_recording_impl(app: tauri::AppHandle) -> Result<(), Str
         }
     };
 
-    let wav_path = match wav_path {
-        Ok(path) => path,
+    let recorded = match recorded {
+        Ok(finished) => {
+            report_input_stats(&app, &finished.stats, false);
+            finished.audio
+        }
         Err(e) => {
             let mut s = state.lock().map_err(|_| "state mutex poisoned".to_string())?;
             s.status.state = "Idle".to_string();
@@ -326,23 +961,10 @@ pub(crate) async fn stop_recording_impl(app: tauri::AppHandle) -> Result<(), Str
         }
     };
 
-    if cfg.azure.api_key.trim().is_empty() {
-        let _ = std::fs::remove_file(&wav_path);
-        let e = "Azure apiKey is empty".to_string();
-        let mut s = state.lock().map_err(|_| "state mutex poisoned".to_string())?;
-        s.status.state = "Idle".to_string();
-        s.status.last_error = Some(e.clone());
-        let status = s.status.clone();
-        drop(s);
-        report_error(&app, "stop_recording", &e);
-        emit_status(&app, &status);
-        return Err(e);
-    }
-
-    let text = match azure_transcribe::transcribe_wav(&wav_path, &cfg).await {
+    let text = match azure_transcribe::transcribe_wav(&recorded, &cfg).await {
         Ok(t) => t,
         Err(e) => {
-            let _ = std::fs::remove_file(&wav_path);
+            recorded.discard();
             let mut s = state.lock().map_err(|_| "state mutex poisoned".to_string())?;
             s.status.state = "Idle".to_string();
             s.status.last_error = Some(e.clone());
@@ -353,6 +975,11 @@ pub(crate) async fn stop_recording_impl(app: tauri::AppHandle) -> Result<(), Str
             return Err(e);
         }
     };
+    let text = if follows_segments && !text.trim().is_empty() {
+        format!(" {}", text.trim_start())
+    } else {
+        text
+    };
 
     let inserting_status = {
         let mut s = state.lock().map_err(|_| "state mutex poisoned".to_string())?;
@@ -361,10 +988,10 @@ pub(crate) async fn stop_recording_impl(app: tauri::AppHandle) -> Result<(), Str
     };
     emit_status(&app, &inserting_status);
 
-    let restore = cfg.insert.restore_clipboard;
+    let insert_cfg = cfg.insert.clone();
     let text2 = text.clone();
     let insert_result = tauri::async_runtime::spawn_blocking(move || {
-        insert::clipboard_paste_restore(&text2, restore)
+        insert::insert_text(&text2, &insert_cfg)
     })
     .await
     .map_err(|e| format!("insert task failed: {e}"));
@@ -372,7 +999,7 @@ pub(crate) async fn stop_recording_impl(app: tauri::AppHandle) -> Result<(), Str
     let insert_result = match insert_result {
         Ok(r) => r,
         Err(e) => {
-            let _ = std::fs::remove_file(&wav_path);
+            recorded.discard();
             let mut s = state.lock().map_err(|_| "state mutex poisoned".to_string())?;
             s.status.state = "Idle".to_string();
             s.status.last_error = Some(e.clone());
@@ -385,7 +1012,7 @@ pub(crate) async fn stop_recording_impl(app: tauri::AppHandle) -> Result<(), Str
     };
 
     if let Err(e) = insert_result {
-        let _ = std::fs::remove_file(&wav_path);
+        recorded.discard();
         let mut s = state.lock().map_err(|_| "state mutex poisoned".to_string())?;
         s.status.state = "Idle".to_string();
         s.status.last_error = Some(e.clone());
@@ -396,7 +1023,7 @@ pub(crate) async fn stop_recording_impl(app: tauri::AppHandle) -> Result<(), Str
         return Err(e);
     }
 
-    let _ = std::fs::remove_file(&wav_path);
+    recorded.discard();
 
     let mut s = state.lock().map_err(|_| "state mutex poisoned".to_string())?;
     s.status.state = "Idle".to_string();
@@ -440,6 +1067,13 @@ pub fn run() {
                         .into(),
                 )
             })?;
+            if let Err(e) = clear_temp_dir(&denoise_comparison_dir()) {
+                let _ = logger::append_error(app.handle(), "setup:denoise_comparisons", &e);
+            }
+            if let Err(e) = clear_temp_dir(&meeting_channels_dir()) {
+                let _ = logger::append_error(app.handle(), "setup:meeting_channels", &e);
+            }
+            spawn_refresh_warm_input(app.handle().clone(), false);
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -449,8 +1083,16 @@ pub fn run() {
             reset_config,
             get_autostart_enabled,
             set_autostart_enabled,
+            list_input_devices,
+            list_output_devices,
+            list_loopback_devices,
             test_transcription,
+            test_microphone,
+            record_denoise_comparison,
+            transcribe_file,
             toggle_recording,
+            pause_recording,
+            resume_recording,
             stop_recording
         ])
         .run(tauri::generate_context!())
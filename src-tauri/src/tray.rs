@@ -1,5 +1,5 @@
-use crate::toggle_recording_impl;
-use crate::app_state::Status;
+use crate::{toggle_pause_impl, toggle_recording_impl};
+use crate::app_state::{RecordingMode, Status};
 use crate::logger;
 use resvg::{tiny_skia, usvg};
 use std::sync::OnceLock;
@@ -10,10 +10,12 @@ use tauri::{AppHandle, Manager};
 const TRAY_ID: &str = "main";
 const ICON_SIZE: u32 = 32;
 const TOGGLE_MENU_ID: &str = "toggle";
+const PAUSE_MENU_ID: &str = "pause";
 
 // Lucide icons (MIT License) - https://lucide.dev/
 const MIC_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><path d="M2 13a2 2 0 0 0 2-2V7a2 2 0 0 1 4 0v13a2 2 0 0 0 4 0V4a2 2 0 0 1 4 0v13a2 2 0 0 0 4 0v-4a2 2 0 0 1 2-2" /></svg>"#;
 const DISC_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><circle cx="12" cy="12" r="10" /><circle cx="12" cy="12" r="2" /></svg>"#;
+const PAUSE_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><rect x="14" y="4" width="4" height="16" rx="1" /><rect x="6" y="4" width="4" height="16" rx="1" /></svg>"#;
 const LOADER_CIRCLE_SVG: &str =
     r#"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><path d="M21 12a9 9 0 1 1-6.219-8.56" /></svg>"#;
 const CIRCLE_ALERT_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><circle cx="12" cy="12" r="10" /><line x1="12" x2="12" y1="8" y2="12" /><line x1="12" x2="12.01" y1="16" y2="16" /></svg>"#;
@@ -28,12 +30,14 @@ const TRAY_STROKE: &str = "#ffffff";
 struct TrayIcons {
     idle: tauri::image::Image<'static>,
     recording: tauri::image::Image<'static>,
+    paused: tauri::image::Image<'static>,
     busy: tauri::image::Image<'static>,
     error: tauri::image::Image<'static>,
 }
 
 static ICONS: OnceLock<Result<TrayIcons, String>> = OnceLock::new();
 static TOGGLE_ITEM: OnceLock<tauri::menu::MenuItem<tauri::Wry>> = OnceLock::new();
+static PAUSE_ITEM: OnceLock<tauri::menu::MenuItem<tauri::Wry>> = OnceLock::new();
 
 fn lucide_svg_with_stroke(svg: &str, stroke: &str) -> String {
     svg.replace(r#"stroke="currentColor""#, &format!(r#"stroke="{stroke}""#))
@@ -78,6 +82,7 @@ fn icons() -> tauri::Result<&'static TrayIcons> {
         Ok::<_, String>(TrayIcons {
             idle: render_svg_icon(MIC_SVG).map_err(|e| e.to_string())?,
             recording: render_svg_icon(DISC_SVG).map_err(|e| e.to_string())?,
+            paused: render_svg_icon(PAUSE_SVG).map_err(|e| e.to_string())?,
             busy: render_svg_icon(LOADER_CIRCLE_SVG).map_err(|e| e.to_string())?,
             error: render_svg_icon(CIRCLE_ALERT_SVG).map_err(|e| e.to_string())?,
         })
@@ -90,7 +95,7 @@ fn icons() -> tauri::Result<&'static TrayIcons> {
 }
 
 fn toggle_menu_state(status: &Status) -> (&'static str, bool) {
-    if status.state == "Recording" {
+    if status.is_recording() {
         return ("Stop", true);
     }
 
@@ -98,6 +103,14 @@ fn toggle_menu_state(status: &Status) -> (&'static str, bool) {
     ("Start", !busy)
 }
 
+fn pause_menu_state(status: &Status) -> (&'static str, bool) {
+    match status.state.as_str() {
+        "Recording" => ("Pause", true),
+        "Paused" => ("Resume", true),
+        _ => ("Pause", false),
+    }
+}
+
 pub fn update_for_status(app: &AppHandle, status: &Status) -> tauri::Result<()> {
     let Some(tray) = app.tray_by_id(TRAY_ID) else {
         return Ok(());
@@ -109,7 +122,9 @@ pub fn update_for_status(app: &AppHandle, status: &Status) -> tauri::Result<()>
     } else {
         match status.state.as_str() {
             "Recording" => (&icons.recording, "Recording"),
+            "Paused" => (&icons.paused, "Paused"),
             "Transcribing" | "Inserting" => (&icons.busy, status.state.as_str()),
+            _ if status.mic_open => (&icons.idle, "Idle, microphone open"),
             _ => (&icons.idle, "Idle"),
         }
     };
@@ -122,17 +137,24 @@ pub fn update_for_status(app: &AppHandle, status: &Status) -> tauri::Result<()>
         let _ = item.set_text(text);
         let _ = item.set_enabled(enabled);
     }
+    if let Some(item) = PAUSE_ITEM.get() {
+        let (text, enabled) = pause_menu_state(status);
+        let _ = item.set_text(text);
+        let _ = item.set_enabled(enabled);
+    }
     Ok(())
 }
 
 pub fn setup(app: &AppHandle) -> tauri::Result<()> {
     let toggle = MenuItemBuilder::with_id(TOGGLE_MENU_ID, "Start").build(app)?;
     let _ = TOGGLE_ITEM.set(toggle.clone());
+    let pause = MenuItemBuilder::with_id(PAUSE_MENU_ID, "Pause").enabled(false).build(app)?;
+    let _ = PAUSE_ITEM.set(pause.clone());
     let settings = MenuItemBuilder::with_id("settings", "Settings").build(app)?;
     let open_logs = MenuItemBuilder::with_id("open_logs", "Open Logs").build(app)?;
     let quit = MenuItemBuilder::with_id("quit", "Quit").build(app)?;
 
-    let menu = Menu::with_items(app, &[&toggle, &settings, &open_logs, &quit])?;
+    let menu = Menu::with_items(app, &[&toggle, &pause, &settings, &open_logs, &quit])?;
 
     let icon = icons()?.idle.clone();
 
@@ -144,9 +166,12 @@ pub fn setup(app: &AppHandle) -> tauri::Result<()> {
             "toggle" => {
                 let app = app.clone();
                 tauri::async_runtime::spawn(async move {
-                    let _ = toggle_recording_impl(app).await;
+                    let _ = toggle_recording_impl(app, RecordingMode::Toggle).await;
                 });
             }
+            "pause" => {
+                let _ = toggle_pause_impl(app);
+            }
             "settings" => {
                 if let Some(window) = app.get_webview_window("main") {
                     let _ = window.show();
@@ -184,6 +209,11 @@ mod tests {
         assert_eq!(disc.height(), ICON_SIZE);
         assert_eq!(disc.rgba().len(), (ICON_SIZE * ICON_SIZE * 4) as usize);
 
+        let pause = render_svg_icon(PAUSE_SVG).expect("pause icon");
+        assert_eq!(pause.width(), ICON_SIZE);
+        assert_eq!(pause.height(), ICON_SIZE);
+        assert_eq!(pause.rgba().len(), (ICON_SIZE * ICON_SIZE * 4) as usize);
+
         let loader = render_svg_icon(LOADER_CIRCLE_SVG).expect("loader icon");
         assert_eq!(loader.width(), ICON_SIZE);
         assert_eq!(loader.height(), ICON_SIZE);
@@ -200,16 +230,40 @@ mod tests {
         let mut status = Status {
             state: "Idle".to_string(),
             last_error: None,
+            mic_open: false,
+            input_stats: None,
+            input_warning: None,
         };
         assert_eq!(toggle_menu_state(&status), ("Start", true));
 
         status.state = "Recording".to_string();
         assert_eq!(toggle_menu_state(&status), ("Stop", true));
 
+        status.state = "Paused".to_string();
+        assert_eq!(toggle_menu_state(&status), ("Stop", true));
+
         status.state = "Transcribing".to_string();
         assert_eq!(toggle_menu_state(&status), ("Start", false));
 
         status.state = "Inserting".to_string();
         assert_eq!(toggle_menu_state(&status), ("Start", false));
     }
+
+    #[test]
+    fn pause_menu_state_maps_status() {
+        let mut status = Status {
+            state: "Idle".to_string(),
+            last_error: None,
+            mic_open: false,
+            input_stats: None,
+            input_warning: None,
+        };
+        assert_eq!(pause_menu_state(&status), ("Pause", false));
+
+        status.state = "Recording".to_string();
+        assert_eq!(pause_menu_state(&status), ("Pause", true));
+
+        status.state = "Paused".to_string();
+        assert_eq!(pause_menu_state(&status), ("Resume", true));
+    }
 }
@@ -14,10 +14,16 @@ vi.mock("@tauri-apps/api/event", () => ({
 
 const baseConfig = {
   azure: { endpoint: "", deployment: "", apiVersion: "2025-03-01-preview", apiKey: "" },
-  hotkey: { windows: "RightCtrl" },
+  hotkey: { windows: "RightCtrl", pauseWindows: "Ctrl+Alt+P" },
   thresholds: { holdMs: 180, doubleClickMs: 300 },
-  recording: { maxSeconds: 120 },
-  insert: { restoreClipboard: false, postfix: "none" },
+  recording: { maxSeconds: 120, capture: "microphone", loopbackDevice: null },
+  insert: {
+    restoreClipboard: false,
+    postfix: "none",
+    pasteChord: "ctrlV",
+    linuxBackend: "auto",
+    typeText: false,
+  },
   sound: { enabled: true },
 };
 
@@ -36,6 +42,10 @@ function mockInvoke() {
         return Promise.resolve(null);
       case "reset_config":
         return Promise.resolve(baseConfig);
+      case "list_output_devices":
+        return Promise.resolve([{ name: "Speakers", isDefault: true }]);
+      case "list_loopback_devices":
+        return Promise.resolve([{ name: "Default output monitor", isDefault: true }]);
       default:
         return Promise.reject(new Error(`Unexpected invoke: ${cmd}`));
     }
@@ -71,6 +81,22 @@ describe("App", () => {
     fireEvent.click(restore);
     expect(restore).toBeChecked();
 
+    const loopback = screen.getByLabelText(/System audio device/i);
+    expect(loopback).toBeDisabled();
+    fireEvent.change(screen.getByLabelText(/^Capture$/i), { target: { value: "meeting" } });
+    expect(loopback).not.toBeDisabled();
+    await waitFor(() => expect(screen.getByRole("option", { name: "Default output monitor" })).toBeInTheDocument());
+    fireEvent.change(loopback, { target: { value: "Default output monitor" } });
+
+    fireEvent.click(screen.getByRole("button", { name: /^Save$/i }));
+    await waitFor(() =>
+      expect(vi.mocked(invoke)).toHaveBeenCalledWith("set_config", {
+        config: expect.objectContaining({
+          recording: expect.objectContaining({ capture: "meeting", loopbackDevice: "Default output monitor" }),
+        }),
+      }),
+    );
+
     fireEvent.click(autostart);
 
     await waitFor(() =>
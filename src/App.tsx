@@ -4,6 +4,9 @@ import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 
 type InsertPostfix = "none";
+type PasteChord = "ctrlV" | "ctrlShiftV";
+type LinuxInsertBackend = "auto" | "xtest" | "uinput";
+type CaptureMode = "microphone" | "systemAudio" | "meeting";
 
 type Config = {
   azure: {
@@ -14,6 +17,7 @@ type Config = {
   };
   hotkey: {
     windows: string;
+    pauseWindows: string;
   };
   thresholds: {
     holdMs: number;
@@ -21,27 +25,66 @@ type Config = {
   };
   recording: {
     maxSeconds: number;
+    capture: CaptureMode;
+    loopbackDevice: string | null;
   };
   insert: {
     restoreClipboard: boolean;
     postfix: InsertPostfix;
+    pasteChord: PasteChord;
+    linuxBackend: LinuxInsertBackend;
+    typeText: boolean;
   };
   sound: {
     enabled: boolean;
   };
 };
 
+type AudioLevel = {
+  rms: number;
+  peak: number;
+};
+
+type OutputDevice = {
+  name: string;
+  isDefault: boolean;
+};
+
+type LoopbackDevice = {
+  name: string;
+  isDefault: boolean;
+};
+
+type MicrophoneTest = {
+  outputDevice: string;
+  stats: {
+    seconds: number;
+    peakDb: number;
+    speechDb: number;
+    snrDb: number;
+  };
+  warnings: string[];
+};
+
 type Status = {
   state: string;
   lastError?: string | null;
+  micOpen?: boolean;
+  inputWarning?: string | null;
 };
 
 const defaultConfig: Config = {
   azure: { endpoint: "", deployment: "", apiVersion: "2025-03-01-preview", apiKey: "" },
-  hotkey: { windows: "RightCtrl" },
+  hotkey: { windows: "RightCtrl", pauseWindows: "Ctrl+Alt+P" },
   thresholds: { holdMs: 180, doubleClickMs: 300 },
-  recording: { maxSeconds: 120 },
-  insert: { restoreClipboard: true, postfix: "none" },
+  recording: { maxSeconds: 120, capture: "microphone", loopbackDevice: null },
+  insert: {
+    restoreClipboard: true,
+    postfix: "none",
+    pasteChord: "ctrlV",
+    linuxBackend: "auto",
+    typeText: false,
+  },
   sound: { enabled: true },
 };
 
@@ -142,6 +185,12 @@ function Switch({
   );
 }
 
+// Maps a linear level to a meter position over a -60..0 dBFS range.
+function levelPercent(linear: number): number {
+  const db = 20 * Math.log10(Math.max(linear, 1e-6));
+  return Math.min(100, Math.max(0, ((db + 60) / 60) * 100));
+}
+
 function App() {
   const [config, setConfig] = useState<Config>(defaultConfig);
   const [status, setStatus] = useState<Status>({ state: "Idle", lastError: null });
@@ -149,6 +198,13 @@ function App() {
   const [loading, setLoading] = useState(true);
   const [saving, setSaving] = useState(false);
   const [testResult, setTestResult] = useState<string | null>(null);
+  const [level, setLevel] = useState<AudioLevel | null>(null);
+  const [limitWarning, setLimitWarning] = useState<number | null>(null);
+  const [outputDevices, setOutputDevices] = useState<OutputDevice[]>([]);
+  const [outputDevice, setOutputDevice] = useState<string>("");
+  const [loopbackDevices, setLoopbackDevices] = useState<LoopbackDevice[]>([]);
+  const [micTest, setMicTest] = useState<MicrophoneTest | null>(null);
+  const [micTesting, setMicTesting] = useState(false);
 
   const canSave = useMemo(() => !loading && !saving, [loading, saving]);
   const isBusy = loading || saving;
@@ -165,6 +221,8 @@ function App() {
       setStatus(loadedStatus);
       const enabled = await invoke<boolean>("get_autostart_enabled");
       setAutostartEnabled(enabled);
+      setOutputDevices(await invoke<OutputDevice[]>("list_output_devices"));
+      setLoopbackDevices(await invoke<LoopbackDevice[]>("list_loopback_devices"));
     } catch (e) {
       console.error(e);
     } finally {
@@ -207,6 +265,21 @@ function App() {
     }
   }
 
+  async function testMicrophone() {
+    setMicTest(null);
+    setMicTesting(true);
+    try {
+      const result = await invoke<MicrophoneTest>("test_microphone", {
+        outputDevice: outputDevice || null,
+      });
+      setMicTest(result);
+    } catch (e) {
+      console.error(e);
+    } finally {
+      setMicTesting(false);
+    }
+  }
+
   useEffect(() => {
     void reload();
   }, []);
@@ -214,17 +287,39 @@ function App() {
   useEffect(() => {
     const unlistenStatus = listen<Status>("status_changed", (event) => {
       setStatus(event.payload);
+      if (event.payload.state !== "Recording") {
+        setLevel(null);
+      }
+      if (event.payload.state !== "Recording" && event.payload.state !== "Paused") {
+        setLimitWarning(null);
+      }
+    });
+    const unlistenLevel = listen<AudioLevel>("audio_level", (event) => {
+      setLevel(event.payload);
     });
     const unlistenTranscript = listen<string>("transcript_ready", (event) => {
       setTestResult(event.payload);
     });
+    const unlistenLimit = listen<number>("recording_limit_warning", (event) => {
+      setLimitWarning(event.payload);
+    });
 
     return () => {
       void unlistenStatus.then((f) => f());
+      void unlistenLevel.then((f) => f());
       void unlistenTranscript.then((f) => f());
+      void unlistenLimit.then((f) => f());
     };
   }, []);
 
+  async function togglePause() {
+    try {
+      await invoke(status.state === "Paused" ? "resume_recording" : "pause_recording");
+    } catch (e) {
+      console.error(e);
+    }
+  }
+
   async function setAutostart(next: boolean) {
     try {
       await invoke("set_autostart_enabled", { enabled: next });
@@ -251,7 +346,45 @@ function App() {
             <span className="inline-flex items-center gap-2 rounded-full border border-slate-200 bg-white px-3 py-1 text-sm text-slate-700 shadow-sm dark:border-slate-800 dark:bg-slate-900 dark:text-slate-200">
               <span className="h-2 w-2 rounded-full bg-emerald-500" />
               Status: <span className="font-medium">{status.state}</span>
+              {status.state === "Recording" && level ? (
+                <span
+                  className="relative h-2 w-20 overflow-hidden rounded-full bg-slate-200 dark:bg-slate-800"
+                  aria-label="Input level"
+                >
+                  <span
+                    className="absolute inset-y-0 left-0 bg-emerald-500"
+                    style={{ width: `${levelPercent(level.rms)}%` }}
+                  />
+                  <span
+                    className="absolute inset-y-0 w-0.5 bg-slate-500"
+                    style={{ left: `${levelPercent(level.peak)}%` }}
+                  />
+                </span>
+              ) : null}
             </span>
+            {status.state === "Recording" || status.state === "Paused" ? (
+              <Button onClick={() => void togglePause()}>
+                {status.state === "Paused" ? "Resume" : "Pause"}
+              </Button>
+            ) : null}
+            {limitWarning !== null ? (
+              <span className="inline-flex items-center gap-2 rounded-full border border-amber-200 bg-amber-50 px-3 py-1 text-sm text-amber-800 shadow-sm dark:border-amber-900 dark:bg-amber-950 dark:text-amber-200">
+                <span className="h-2 w-2 rounded-full bg-amber-500" />
+                Recording stops in {limitWarning} s
+              </span>
+            ) : null}
+            {status.micOpen && status.state === "Idle" ? (
+              <span className="inline-flex items-center gap-2 rounded-full border border-amber-200 bg-amber-50 px-3 py-1 text-sm text-amber-800 shadow-sm dark:border-amber-900 dark:bg-amber-950 dark:text-amber-200">
+                <span className="h-2 w-2 rounded-full bg-amber-500" />
+                Microphone open (pre-roll)
+              </span>
+            ) : null}
+            {status.inputWarning ? (
+              <span className="inline-flex items-center gap-2 rounded-full border border-amber-200 bg-amber-50 px-3 py-1 text-sm text-amber-800 shadow-sm dark:border-amber-900 dark:bg-amber-950 dark:text-amber-200">
+                <span className="h-2 w-2 rounded-full bg-amber-500" />
+                {status.inputWarning}
+              </span>
+            ) : null}
           </div>
         </header>
 
@@ -371,13 +504,30 @@ function App() {
                 />
               </label>
 
+              <label className="block">
+                <span className="text-sm font-medium text-slate-800 dark:text-slate-200">
+                  Windows pause hotkey
+                </span>
+                <Input
+                  value={config.hotkey.pauseWindows}
+                  onChange={(value) => {
+                    setConfig((prev) => ({
+                      ...prev,
+                      hotkey: { ...prev.hotkey, pauseWindows: value },
+                    }));
+                  }}
+                  placeholder="Ctrl+Alt+P"
+                />
+              </label>
+
               <div className="rounded-xl border border-slate-200 bg-slate-50 p-3 text-sm text-slate-700 dark:border-slate-800 dark:bg-slate-950 dark:text-slate-200">
                 <div className="font-medium text-slate-800 dark:text-slate-200">macOS shortcut</div>
                 <div className="mt-1">
                   Use <code className="rounded bg-white px-1.5 py-0.5 dark:bg-slate-900">Language (Globe/Fn)</code>{" "}
                   key:
                   <span className="ml-2 text-slate-600 dark:text-slate-300">
-                    hold = push-to-talk, double-click = toggle.
+                    hold = push-to-talk, double-click = toggle. Control+Option+P pauses or resumes a toggle
+                    recording.
                   </span>
                 </div>
               </div>
@@ -422,40 +572,141 @@ function App() {
             </div>
           </Card>
 
-          <Card title="Recording" description="Safety limit for max recording duration.">
-            <label>
-              <span className="text-sm font-medium text-slate-800 dark:text-slate-200">
-                Max seconds
-              </span>
-              <Input
-                type="number"
-                min={1}
-                value={config.recording.maxSeconds}
-                onChange={(value) => {
-                  setConfig((prev) => ({
-                    ...prev,
-                    recording: { ...prev.recording, maxSeconds: Number(value) },
-                  }));
-                }}
-              />
-            </label>
+          <Card title="Recording" description="What to record, and a safety limit for its duration.">
+            <div className="space-y-4">
+              <label className="flex items-center justify-between gap-4">
+                <span className="text-sm font-medium text-slate-800 dark:text-slate-200">
+                  Capture
+                </span>
+                <select
+                  className="rounded-xl border border-slate-200 bg-white px-3 py-2 text-sm text-slate-900 shadow-sm dark:border-slate-800 dark:bg-slate-950 dark:text-slate-50"
+                  value={config.recording.capture}
+                  onChange={(e) => {
+                    const capture = e.currentTarget.value as CaptureMode;
+                    setConfig((prev) => ({
+                      ...prev,
+                      recording: { ...prev.recording, capture },
+                    }));
+                  }}
+                >
+                  <option value="microphone">Microphone</option>
+                  <option value="systemAudio">System audio</option>
+                  <option value="meeting">Meeting (microphone + system audio)</option>
+                </select>
+              </label>
+              <label className="flex items-center justify-between gap-4">
+                <span className="text-sm font-medium text-slate-800 dark:text-slate-200">
+                  System audio device
+                </span>
+                <select
+                  className="rounded-xl border border-slate-200 bg-white px-3 py-2 text-sm text-slate-900 shadow-sm dark:border-slate-800 dark:bg-slate-950 dark:text-slate-50"
+                  value={config.recording.loopbackDevice ?? ""}
+                  disabled={config.recording.capture === "microphone"}
+                  onChange={(e) => {
+                    const loopbackDevice = e.currentTarget.value || null;
+                    setConfig((prev) => ({
+                      ...prev,
+                      recording: { ...prev.recording, loopbackDevice },
+                    }));
+                  }}
+                >
+                  <option value="">Default output</option>
+                  {loopbackDevices.map((device) => (
+                    <option key={device.name} value={device.name}>
+                      {device.name}
+                    </option>
+                  ))}
+                </select>
+              </label>
+              <label className="block">
+                <span className="text-sm font-medium text-slate-800 dark:text-slate-200">
+                  Max seconds
+                </span>
+                <Input
+                  type="number"
+                  min={1}
+                  value={config.recording.maxSeconds}
+                  onChange={(value) => {
+                    setConfig((prev) => ({
+                      ...prev,
+                      recording: { ...prev.recording, maxSeconds: Number(value) },
+                    }));
+                  }}
+                />
+              </label>
+            </div>
           </Card>
 
           <Card title="Insert" description="Clipboard behavior after pasting transcription.">
-            <label className="flex items-center justify-between gap-4">
-              <span className="text-sm font-medium text-slate-800 dark:text-slate-200">
-                Restore clipboard after paste
-              </span>
-              <Switch
-                checked={config.insert.restoreClipboard}
-                onChange={(checked) => {
-                  setConfig((prev) => ({
-                    ...prev,
-                    insert: { ...prev.insert, restoreClipboard: checked },
-                  }));
-                }}
-              />
-            </label>
+            <div className="space-y-4">
+              <label className="flex items-center justify-between gap-4">
+                <span className="text-sm font-medium text-slate-800 dark:text-slate-200">
+                  Restore clipboard after paste
+                </span>
+                <Switch
+                  checked={config.insert.restoreClipboard}
+                  onChange={(checked) => {
+                    setConfig((prev) => ({
+                      ...prev,
+                      insert: { ...prev.insert, restoreClipboard: checked },
+                    }));
+                  }}
+                />
+              </label>
+              <label className="flex items-center justify-between gap-4">
+                <span className="text-sm font-medium text-slate-800 dark:text-slate-200">
+                  Paste keys (Linux)
+                </span>
+                <select
+                  className="rounded-xl border border-slate-200 bg-white px-3 py-2 text-sm text-slate-900 shadow-sm dark:border-slate-800 dark:bg-slate-950 dark:text-slate-50"
+                  value={config.insert.pasteChord}
+                  onChange={(e) => {
+                    const pasteChord = e.currentTarget.value as PasteChord;
+                    setConfig((prev) => ({
+                      ...prev,
+                      insert: { ...prev.insert, pasteChord },
+                    }));
+                  }}
+                >
+                  <option value="ctrlV">Ctrl+V</option>
+                  <option value="ctrlShiftV">Ctrl+Shift+V (terminals)</option>
+                </select>
+              </label>
+              <label className="flex items-center justify-between gap-4">
+                <span className="text-sm font-medium text-slate-800 dark:text-slate-200">
+                  Key input (Linux)
+                </span>
+                <select
+                  className="rounded-xl border border-slate-200 bg-white px-3 py-2 text-sm text-slate-900 shadow-sm dark:border-slate-800 dark:bg-slate-950 dark:text-slate-50"
+                  value={config.insert.linuxBackend}
+                  onChange={(e) => {
+                    const linuxBackend = e.currentTarget.value as LinuxInsertBackend;
+                    setConfig((prev) => ({
+                      ...prev,
+                      insert: { ...prev.insert, linuxBackend },
+                    }));
+                  }}
+                >
+                  <option value="auto">Automatic</option>
+                  <option value="xtest">XTest (X11)</option>
+                  <option value="uinput">Virtual keyboard (Wayland)</option>
+                </select>
+              </label>
+              <label className="flex items-center justify-between gap-4">
+                <span className="text-sm font-medium text-slate-800 dark:text-slate-200">
+                  Type text instead of pasting (Linux)
+                </span>
+                <Switch
+                  checked={config.insert.typeText}
+                  onChange={(checked) => {
+                    setConfig((prev) => ({
+                      ...prev,
+                      insert: { ...prev.insert, typeText: checked },
+                    }));
+                  }}
+                />
+              </label>
+            </div>
           </Card>
 
           <Card title="Sound" description="Prompt sound effects for recording actions.">
@@ -487,9 +738,40 @@ function App() {
             <Button onClick={() => void testTranscription()} disabled={isBusy}>
               Test connection (3s)
             </Button>
+            <select
+              className="rounded-xl border border-slate-200 bg-white px-3 py-2 text-sm text-slate-900 shadow-sm dark:border-slate-800 dark:bg-slate-950 dark:text-slate-50"
+              value={outputDevice}
+              onChange={(e) => setOutputDevice(e.currentTarget.value)}
+            >
+              <option value="">Default output</option>
+              {outputDevices.map((device) => (
+                <option key={device.name} value={device.name}>
+                  {device.name}
+                </option>
+              ))}
+            </select>
+            <Button onClick={() => void testMicrophone()} disabled={isBusy || micTesting}>
+              {micTesting ? "Testing microphone…" : "Test microphone (3s)"}
+            </Button>
           </div>
         </div>
 
+        {micTest ? (
+          <div className="mt-6">
+            <Card title="Microphone test" description={`Played back on ${micTest.outputDevice}.`}>
+              <p className="text-sm text-slate-800 dark:text-slate-200">
+                Peak {micTest.stats.peakDb.toFixed(1)} dBFS, speech {micTest.stats.speechDb.toFixed(1)} dBFS,
+                SNR {micTest.stats.snrDb.toFixed(1)} dB over {micTest.stats.seconds.toFixed(1)} s.
+              </p>
+              {micTest.warnings.map((warning) => (
+                <p key={warning} className="mt-2 text-sm text-amber-700 dark:text-amber-300">
+                  {warning}
+                </p>
+              ))}
+            </Card>
+          </div>
+        ) : null}
+
         {testResult ? (
           <div className="mt-6">
             <Card title="Transcript">